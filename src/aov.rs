@@ -0,0 +1,156 @@
+use alloc::{format, vec, vec::Vec};
+use ndless::{
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use crate::{
+    export::{vec3_channel, write_pfm, write_pgm16},
+    fixed::FixedI32,
+    hittable::HitRecord,
+    ray::Ray,
+    scalar::Scalar,
+    vec3::Vec3FI32,
+};
+
+/// Object id written for pixels whose primary ray doesn't hit anything.
+pub const NO_OBJECT: u16 = u16::MAX;
+
+/// The extra outputs that can be requested alongside the final color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aov {
+    /// Distance from the camera to the first hit.
+    Depth,
+    /// Surface normal at the first hit (facing the camera).
+    Normal,
+    /// Base color of the material at the first hit.
+    Albedo,
+    /// Index of the object in the scene that was hit first.
+    ObjectId,
+}
+
+/// Arbitrary output vectors: per-pixel information about the first hit of every pixel's primary
+/// ray, collected while rendering. Only the buffers that were asked for get allocated, since a
+/// single Vec3 buffer already takes up ~900K at 320x240.
+pub struct AovBuffers {
+    width: u16,
+    height: u16,
+    pub depth: Option<Vec<FixedI32>>,
+    pub normal: Option<Vec<Vec3FI32>>,
+    pub albedo: Option<Vec<Vec3FI32>>,
+    pub object_id: Option<Vec<u16>>,
+}
+
+impl AovBuffers {
+    pub fn new(width: u16, height: u16, aovs: &[Aov]) -> Self {
+        let len = width as usize * height as usize;
+        let wants = |aov| aovs.contains(&aov);
+
+        Self {
+            width,
+            height,
+            depth: if wants(Aov::Depth) {
+                Some(vec![FixedI32::default(); len])
+            } else {
+                None
+            },
+            normal: if wants(Aov::Normal) {
+                Some(vec![Vec3FI32::default(); len])
+            } else {
+                None
+            },
+            albedo: if wants(Aov::Albedo) {
+                Some(vec![Vec3FI32::default(); len])
+            } else {
+                None
+            },
+            object_id: if wants(Aov::ObjectId) {
+                Some(vec![NO_OBJECT; len])
+            } else {
+                None
+            },
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Records the first hit of the primary ray through pixel `index`.
+    pub fn store_hit<T: Scalar>(&mut self, index: usize, ray: &Ray<T>, rec: &HitRecord<T>) {
+        if let Some(ref mut depth) = self.depth {
+            depth[index] = (rec.t * ray.dir().mag()).to_fixed();
+        }
+
+        if let Some(ref mut normal) = self.normal {
+            normal[index] = rec.normal.to_fixed();
+        }
+
+        if let Some(ref mut albedo) = self.albedo {
+            albedo[index] = match rec.material {
+                Some(ref material) => material.albedo(rec).to_fixed(),
+                None => Vec3FI32::default(),
+            };
+        }
+
+        if let Some(ref mut object_id) = self.object_id {
+            object_id[index] = rec.object_id;
+        }
+    }
+
+    /// Records a pixel whose primary ray escaped into the sky.
+    pub fn store_miss(&mut self, index: usize, far: FixedI32, sky: Vec3FI32) {
+        if let Some(ref mut depth) = self.depth {
+            depth[index] = far;
+        }
+
+        if let Some(ref mut normal) = self.normal {
+            normal[index] = Vec3FI32::default();
+        }
+
+        // Denoisers expect the background color as the albedo of empty pixels
+        if let Some(ref mut albedo) = self.albedo {
+            albedo[index] = sky;
+        }
+
+        if let Some(ref mut object_id) = self.object_id {
+            object_id[index] = NO_OBJECT;
+        }
+    }
+
+    /// Writes every allocated buffer to `<prefix>_<name>.<ext>.tns`. Depth, normal and albedo are
+    /// written as PFM (what most compositors and denoisers read), object ids as a 16-bit PGM.
+    pub fn export(&self, prefix: &str) -> io::Result<()> {
+        let (w, h) = (self.width as usize, self.height as usize);
+
+        if let Some(ref depth) = self.depth {
+            let mut file = BufWriter::new(File::create(format!("{prefix}_depth.pfm.tns"))?);
+            write_pfm(&mut file, w, h, 1, |i, _| depth[i])?;
+            file.flush()?;
+        }
+
+        if let Some(ref normal) = self.normal {
+            let mut file = BufWriter::new(File::create(format!("{prefix}_normal.pfm.tns"))?);
+            write_pfm(&mut file, w, h, 3, |i, c| vec3_channel(normal[i], c))?;
+            file.flush()?;
+        }
+
+        if let Some(ref albedo) = self.albedo {
+            let mut file = BufWriter::new(File::create(format!("{prefix}_albedo.pfm.tns"))?);
+            write_pfm(&mut file, w, h, 3, |i, c| vec3_channel(albedo[i], c))?;
+            file.flush()?;
+        }
+
+        if let Some(ref object_id) = self.object_id {
+            let mut file = BufWriter::new(File::create(format!("{prefix}_id.pgm.tns"))?);
+            write_pgm16(&mut file, w, h, object_id)?;
+            file.flush()?;
+        }
+
+        Ok(())
+    }
+}
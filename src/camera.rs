@@ -1,34 +1,30 @@
 use oorandom::Rand32;
 
-use crate::{
-    fixed::{self, FixedI32},
-    fxi32,
-    ray::Ray,
-    vec3::Vec3FI32,
-};
+use crate::{fixed::FixedI32, ray::Ray, scalar::Scalar, vec3::Vec3};
 
-pub struct Camera {
-    origin: Vec3FI32,
-    top_left: Vec3FI32,
-    horizontal: Vec3FI32,
-    vertical: Vec3FI32,
-    lens_radius: FixedI32,
-    u: Vec3FI32,
-    v: Vec3FI32,
+pub struct Camera<T = FixedI32> {
+    origin: Vec3<T>,
+    top_left: Vec3<T>,
+    horizontal: Vec3<T>,
+    vertical: Vec3<T>,
+    lens_radius: T,
+    u: Vec3<T>,
+    v: Vec3<T>,
 }
 
-impl Camera {
+impl<T: Scalar> Camera<T> {
     pub fn new(
-        lookfrom: Vec3FI32,
-        lookat: Vec3FI32,
-        vup: Vec3FI32,
-        vfov: FixedI32,
-        aspect_ratio: FixedI32,
-        apeture: FixedI32,
-        focus_dist: FixedI32,
+        lookfrom: Vec3<T>,
+        lookat: Vec3<T>,
+        vup: Vec3<T>,
+        vfov: T,
+        aspect_ratio: T,
+        apeture: T,
+        focus_dist: T,
     ) -> Self {
-        let focal_length = FixedI32::from(1);
-        let viewport_height = focal_length * 2 * (vfov * fixed::PI / 180).tan();
+        let two = T::from_int(2);
+        let focal_length = T::ONE;
+        let viewport_height = focal_length * two * (vfov * T::PI / T::from_int(180)).tan();
         let viewport_width = aspect_ratio * viewport_height;
 
         let w = (lookfrom - lookat).unit_vector();
@@ -39,8 +35,8 @@ impl Camera {
         let horizontal = u * focus_dist * viewport_width;
         let vertical = v * focus_dist * viewport_height;
 
-        let top_left = origin - horizontal / fxi32!(2) + vertical / fxi32!(2) - w * focus_dist;
-        let lens_radius = apeture / 2;
+        let top_left = origin - horizontal / two + vertical / two - w * focus_dist;
+        let lens_radius = apeture / two;
 
         Self {
             origin,
@@ -53,15 +49,15 @@ impl Camera {
         }
     }
 
-    pub fn get_ray_noblur(&self, u: FixedI32, v: FixedI32) -> Ray {
+    pub fn get_ray_noblur(&self, u: T, v: T) -> Ray<T> {
         Ray::new(
             self.origin,
             self.top_left + self.horizontal * u - self.vertical * v - self.origin,
         )
     }
 
-    pub fn get_ray_blur(&self, rand: &mut Rand32, s: FixedI32, t: FixedI32) -> Ray {
-        let rd = Vec3FI32::random_in_unit_disk(rand) * self.lens_radius;
+    pub fn get_ray_blur(&self, rand: &mut Rand32, s: T, t: T) -> Ray<T> {
+        let rd = Vec3::<T>::random_in_unit_disk(rand) * self.lens_radius;
         let offset = self.u * rd.x + self.v * rd.y;
 
         Ray::new(
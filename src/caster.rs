@@ -1,32 +1,139 @@
+use alloc::{vec, vec::Vec};
+use core::{ops::Range, time::Duration};
+use ndless::io::{self, Write};
 use oorandom::Rand32;
 
 use crate::{
+    aov::AovBuffers,
     camera::Camera,
-    dprintln,
+    clock::Stopwatch,
+    dprintln, export,
+    filter::{self, Filter},
     fixed::FixedI32,
+    framebuffer::Framebuffer,
     fxi32,
     hittable::{HitRecord, Hittable, HittableList},
     ray::Ray,
-    vec3::Vec3FI32,
+    scalar::Scalar,
+    stats::RenderStats,
+    tile::Rect,
+    tonemap::ToneMapper,
+    vec3::{Vec3, Vec3FI32},
 };
 
-pub struct Renderer {
-    camera: Camera,
-    scene: HittableList,
+const T_MIN: FixedI32 = FixedI32::from_dec(0, 1, 3);
+const T_MAX: FixedI32 = FixedI32::from_components(50, 0);
+/// Bounces before a path gets cut off.
+const MAX_DEPTH: u8 = 10;
+
+/// An RNG for one sample, seeded from a hash of the render seed, the pixel and the sample's index.
+/// Since no two samples share a stream, a pixel comes out the same no matter what got rendered
+/// before it or how many random numbers the other pixels' materials used up.
+pub fn sample_rng(seed: u64, x: u16, y: u16, sample: u16) -> Rand32 {
+    let mut hash = seed ^ ((x as u64) << 32 | (y as u64) << 16 | sample as u64);
+
+    // SplitMix64's finalizer, so neighbouring pixels get unrelated seeds
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    Rand32::new(hash ^ (hash >> 31))
+}
+
+/// What a progress callback wants the renderer to do next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderControl {
+    Continue,
+    /// Don't render anything, just keep calling the callback until it says otherwise.
+    Pause,
+    /// Stop rendering, keeping whatever got done so far.
+    Abort,
+}
+
+impl RenderControl {
+    /// Calls `poll` for as long as it says to pause, returning whether to keep rendering.
+    fn keep_going(mut poll: impl FnMut() -> RenderControl) -> bool {
+        loop {
+            match poll() {
+                RenderControl::Continue => return true,
+                RenderControl::Pause => {}
+                RenderControl::Abort => return false,
+            }
+        }
+    }
+}
+
+/// What ends up in the framebuffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    Color,
+    /// Intersection tests per sample, to be shown with `heatmap::resolve`.
+    HitTests,
+    /// Bounces per sample, to be shown with `heatmap::resolve`.
+    Bounces,
+}
+
+impl RenderMode {
+    /// The running total of whatever this mode measures.
+    fn cost(self, stats: &RenderStats) -> u64 {
+        match self {
+            RenderMode::Color => 0,
+            RenderMode::HitTests => stats.tests.iter().sum(),
+            RenderMode::Bounces => stats.bounce_rays,
+        }
+    }
+}
+
+/// How a time budgeted render went, see `Renderer::render_timed`.
+#[derive(Clone, Debug)]
+pub struct RenderReport {
+    /// Samples per pixel in every finished pass.
+    pub samples: u16,
+    /// The samples every finished pass took, which get stratified separately. The pixel debugger
+    /// needs these to trace a pixel the way it got rendered.
+    pub passes: Vec<Range<u16>>,
+    pub elapsed: Duration,
+    /// False if the progress callback aborted the render.
+    pub finished: bool,
+}
+
+/// Where a time budgeted render is at, for its progress callback.
+#[derive(Clone, Debug)]
+pub struct TimedProgress {
+    /// The samples of the pass that's being rendered. Every pass before it is finished, so this
+    /// starts at the samples per pixel the image already has.
+    pub pass: Range<u16>,
+    /// Camera rays traced so far, over all passes.
+    pub rays: u64,
+}
+
+/// Renders in `T`, which is `FixedI32` everywhere but in the host's float reference renders.
+/// Sample positions, colors and everything that goes into the framebuffer stay in fixed point
+/// either way, so a float render only differs in the math along the rays.
+pub struct Renderer<T: Scalar = FixedI32> {
+    camera: Camera<T>,
+    scene: HittableList<T>,
     width: u16,
     height: u16,
     samples: u16,
     lens_blur: bool,
+    tone_mapper: ToneMapper,
+    filter: Filter,
+    seed: u64,
+    mode: RenderMode,
 }
 
-impl Renderer {
+impl<T: Scalar> Renderer<T> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        camera: Camera,
-        scene: HittableList,
+        camera: Camera<T>,
+        scene: HittableList<T>,
         width: u16,
         height: u16,
         samples: u16,
         lens_blur: bool,
+        tone_mapper: ToneMapper,
+        filter: Filter,
+        seed: u64,
     ) -> Self {
         Self {
             camera,
@@ -35,111 +142,504 @@ impl Renderer {
             height,
             samples,
             lens_blur,
+            tone_mapper,
+            filter,
+            seed,
+            mode: RenderMode::Color,
         }
     }
 
+    /// Traces `samples` rays through every pixel and adds them to `framebuffer`, on top of whatever
+    /// it already holds. Finished rows get tone mapped into `screen_buff` and `rgb_buff` as they go,
+    /// and `progress_callback` gets called after every one of them.
+    ///
+    /// Returns false if the callback aborted the render. The buffers still get the partial image.
     pub fn render_scene<F>(
         &self,
-        rand: &mut Rand32,
+        framebuffer: &mut Framebuffer,
+        screen_buff: &mut [u16],
+        rgb_buff: &mut [u8],
+        aovs: Option<&mut AovBuffers>,
+        stats: &mut RenderStats,
+        progress_callback: &mut F,
+    ) -> bool
+    where
+        F: FnMut(&mut [u16], u16) -> RenderControl,
+    {
+        self.render_pass(
+            0..self.samples,
+            framebuffer,
+            screen_buff,
+            rgb_buff,
+            aovs,
+            stats,
+            &mut |buffer, row, _| progress_callback(buffer, row),
+        )
+    }
+
+    /// Like `render_scene`, but instead of a fixed sample count it keeps adding passes over the
+    /// whole image until `budget` runs out. The first pass takes one sample per pixel and shows how
+    /// long a sample takes, every pass after that is as big as all the ones before it (so the
+    /// preview keeps visibly getting better), as long as it fits in what's left of the budget.
+    /// `aovs` get filled in on the first pass. The progress callback also gets told which pass
+    /// it's on.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_timed<F>(
+        &self,
+        budget: Duration,
+        framebuffer: &mut Framebuffer,
+        screen_buff: &mut [u16],
+        rgb_buff: &mut [u8],
+        mut aovs: Option<&mut AovBuffers>,
+        stats: &mut RenderStats,
+        progress_callback: &mut F,
+    ) -> RenderReport
+    where
+        F: FnMut(&mut [u16], u16, &TimedProgress) -> RenderControl,
+    {
+        let stopwatch = Stopwatch::start();
+        let mut report = RenderReport {
+            samples: 0,
+            passes: Vec::new(),
+            elapsed: Duration::default(),
+            finished: true,
+        };
+
+        loop {
+            let pass_samples = if report.samples == 0 {
+                1
+            } else {
+                let elapsed = stopwatch.elapsed();
+                let per_sample = elapsed.max(Stopwatch::RESOLUTION) / report.samples as u32;
+                let affordable =
+                    budget.saturating_sub(elapsed).as_micros() / per_sample.as_micros().max(1);
+
+                (affordable.min(report.samples as u128) as u16).min(u16::MAX - report.samples)
+            };
+
+            if pass_samples == 0 {
+                break;
+            }
+
+            let samples = report.samples..(report.samples + pass_samples);
+            if !self.render_pass(
+                samples.clone(),
+                framebuffer,
+                screen_buff,
+                rgb_buff,
+                aovs.take(),
+                stats,
+                &mut |buffer, row, stats| {
+                    let progress = TimedProgress {
+                        pass: samples.clone(),
+                        rays: stats.primary_rays,
+                    };
+
+                    progress_callback(buffer, row, &progress)
+                },
+            ) {
+                report.finished = false;
+                break;
+            }
+
+            report.samples += pass_samples;
+            report.passes.push(samples);
+        }
+
+        report.elapsed = stopwatch.elapsed();
+        report
+    }
+
+    /// Roughly how long one sample per pixel over the whole image takes, measured by tracing every
+    /// 8th row. Good for picking a sample count for renders that can't be refined as they go.
+    pub fn estimate_pass_time(&self) -> Duration {
+        let stopwatch = Stopwatch::start();
+        let mut stats = RenderStats::default();
+
+        for i in (0..self.height).step_by(8) {
+            let mut framebuffer = Framebuffer::new_region(0, i, self.width, 1);
+            self.render_row(i, 0..self.width, 0..1, &mut framebuffer, None, &mut stats);
+        }
+
+        stopwatch.elapsed().max(Stopwatch::RESOLUTION) * 8
+    }
+
+    pub fn set_mode(&mut self, mode: RenderMode) {
+        self.mode = mode;
+    }
+
+    pub fn samples(&self) -> u16 {
+        self.samples
+    }
+
+    pub fn set_samples(&mut self, samples: u16) {
+        self.samples = samples;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_pass<F>(
+        &self,
+        samples: Range<u16>,
+        framebuffer: &mut Framebuffer,
         screen_buff: &mut [u16],
         rgb_buff: &mut [u8],
+        mut aovs: Option<&mut AovBuffers>,
+        stats: &mut RenderStats,
         progress_callback: &mut F,
-    ) where
-        F: FnMut(&mut [u16], u16),
+    ) -> bool
+    where
+        F: FnMut(&mut [u16], u16, &RenderStats) -> RenderControl,
     {
-        let step_v = fxi32!(1) / (self.height as i32);
-        let step_u = fxi32!(1) / (self.width as i32);
+        let mut finished = true;
 
         for i in 0..self.height {
-            for j in 0..self.width {
-                let ray = if self.lens_blur {
-                    self.camera
-                        .get_ray_blur(rand, step_u * (j as i32), step_v * (i as i32))
-                } else {
-                    self.camera
-                        .get_ray_noblur(step_u * (j as i32), step_v * (i as i32))
-                };
-
-                let mut color = Vec3FI32::default();
-                for _ in 0..self.samples {
-                    let new_ray = Ray::new(
-                        ray.origin(),
-                        ray.dir()
-                            + Vec3FI32::new(
-                                FixedI32::rand(rand) / 300,
-                                FixedI32::rand(rand) / 300,
-                                FixedI32::rand(rand) / 300,
-                            ),
+            self.render_row(
+                i,
+                0..self.width,
+                samples.clone(),
+                framebuffer,
+                aovs.as_deref_mut(),
+                stats,
+            );
+
+            // Rows further down still splat into this one, but it's close enough for a preview
+            framebuffer.resolve_row(i, &self.tone_mapper, screen_buff, rgb_buff);
+
+            if !RenderControl::keep_going(|| progress_callback(screen_buff, i, stats)) {
+                finished = false;
+                break;
+            }
+        }
+
+        framebuffer.resolve(&self.tone_mapper, screen_buff, rgb_buff);
+
+        finished
+    }
+
+    /// Renders the image `band_rows` rows at a time and streams it out as a binary PPM, for images
+    /// that are too big to keep a whole framebuffer around (a 1280x960 one would take up ~30M).
+    /// Only a window of `band_rows` plus the reach of the filter above and below is kept in
+    /// memory. `progress_callback` gets every finished row, as RGB888.
+    ///
+    /// Returns false if the callback aborted the render, in which case the rest of the image is
+    /// written out black so that the file is still a valid PPM.
+    pub fn render_to_ppm<W, F>(
+        &self,
+        out: &mut W,
+        band_rows: u16,
+        stats: &mut RenderStats,
+        progress_callback: &mut F,
+    ) -> io::Result<bool>
+    where
+        W: Write,
+        F: FnMut(u16, &[u8]) -> RenderControl,
+    {
+        let margin = self.filter_margin();
+        let mut framebuffer = Framebuffer::new_region(0, 0, self.width, band_rows + 2 * margin);
+        let mut row_buff = vec![0u8; self.width as usize * 3];
+
+        export::write_ppm_header(out, self.width as usize, self.height as usize)?;
+
+        let mut finished = true;
+        let mut next_row = 0;
+        while framebuffer.origin().1 < self.height {
+            let band_end = (next_row + band_rows).min(self.height);
+            if finished {
+                for i in next_row..band_end {
+                    self.render_row(
+                        i,
+                        0..self.width,
+                        0..self.samples,
+                        &mut framebuffer,
+                        None,
+                        stats,
                     );
-                    color += self.ray_color(&new_ray, &self.scene, rand, 10);
                 }
-                color = color / fxi32!(self.samples);
+            }
+            next_row = band_end;
+
+            // Anything further up than the filter reaches won't be getting any more samples
+            let top = framebuffer.origin().1;
+            let done = if next_row == self.height {
+                self.height
+            } else {
+                next_row.saturating_sub(margin).max(top)
+            };
+
+            for i in top..done {
+                let start = (i - top) as usize * self.width as usize;
+
+                for j in 0..self.width as usize {
+                    let (_, rgb888) = framebuffer.resolve_pixel(start + j, &self.tone_mapper);
+                    row_buff[j * 3..j * 3 + 3].copy_from_slice(&rgb888);
+                }
+
+                out.write_all(&row_buff)?;
+
+                if finished {
+                    finished = RenderControl::keep_going(|| progress_callback(i, &row_buff));
+                }
+            }
+
+            framebuffer.scroll(done - top);
+        }
+
+        Ok(finished)
+    }
 
-                // 1/2 Gamma correction
-                color.x = color.x.sqrt();
-                color.y = color.y.sqrt();
-                color.z = color.z.sqrt();
+    /// Traces just the pixels inside `rect`, adding them to `framebuffer` (which can cover the whole
+    /// image or any part of it, see `Rect::grow` for what a region framebuffer should cover).
+    /// `progress_callback` gets called with the image row that was just finished.
+    ///
+    /// Returns false if the callback aborted the render.
+    pub fn render_region<F>(
+        &self,
+        rect: Rect,
+        framebuffer: &mut Framebuffer,
+        mut aovs: Option<&mut AovBuffers>,
+        stats: &mut RenderStats,
+        progress_callback: &mut F,
+    ) -> bool
+    where
+        F: FnMut(&Framebuffer, u16) -> RenderControl,
+    {
+        for i in rect.y..(rect.y + rect.height) {
+            self.render_row(
+                i,
+                rect.x..(rect.x + rect.width),
+                0..self.samples,
+                framebuffer,
+                aovs.as_deref_mut(),
+                stats,
+            );
 
-                // RGB555
-                let r: i32 = (color.x * 0x1f).into();
-                let g: i32 = (color.y * 0x1f).into();
-                let b: i32 = (color.z * 0x1f).into();
+            if !RenderControl::keep_going(|| progress_callback(framebuffer, i)) {
+                return false;
+            }
+        }
 
-                let r = r.clamp(0, 0x1f) as u16;
-                let g = g.clamp(0, 0x1f) as u16;
-                let b = b.clamp(0, 0x1f) as u16;
+        true
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
 
-                let index = (i as usize) * (self.width as usize) + (j as usize);
+    pub fn height(&self) -> u16 {
+        self.height
+    }
 
-                screen_buff[index] = r << 10 | g << 5 | b;
+    /// How many pixels away from where it was taken a sample can still land.
+    pub fn filter_margin(&self) -> u16 {
+        let reach: i32 = self.filter.radius().into();
+        reach as u16 + 1
+    }
+
+    /// Traces the pixels in `cols` of image row `row` into `framebuffer` (and `aovs`, which cover
+    /// the same part of the image as the framebuffer). `samples` are the indices of the samples to
+    /// take, which get stratified among themselves.
+    fn render_row(
+        &self,
+        row: u16,
+        cols: Range<u16>,
+        samples: Range<u16>,
+        framebuffer: &mut Framebuffer,
+        mut aovs: Option<&mut AovBuffers>,
+        stats: &mut RenderStats,
+    ) {
+        let stopwatch = Stopwatch::start();
+        let (width, height) = (self.width as i32, self.height as i32);
+        let (fb_x, fb_y) = framebuffer.origin();
+        let (t_min, t_max) = (T::from_fixed(T_MIN), T::from_fixed(T_MAX));
+
+        for j in cols {
+            if let Some(aovs) = aovs.as_deref_mut() {
+                let index =
+                    (row - fb_y) as usize * framebuffer.width() as usize + (j - fb_x) as usize;
+                let ray = self.camera.get_ray_noblur(
+                    T::from_fixed((fxi32!(0.5) + j as i32) / width),
+                    T::from_fixed((fxi32!(0.5) + row as i32) / height),
+                );
+                let mut rec = HitRecord::default();
+
+                if self.scene.hit(&ray, &mut rec, t_min, t_max) {
+                    aovs.store_hit(index, &ray, &rec);
+                } else {
+                    aovs.store_miss(
+                        index,
+                        (t_max * ray.dir().mag()).to_fixed(),
+                        Self::sky_color(&ray).to_fixed(),
+                    );
+                }
+            }
 
-                // RGB888
-                let r: i32 = (color.x * 0xff).into();
-                let g: i32 = (color.y * 0xff).into();
-                let b: i32 = (color.z * 0xff).into();
+            for sample in samples.clone() {
+                let (mut rand, x, y, ray) = self.camera_sample(j, row, sample, &samples);
+                let cost = self.mode.cost(stats);
 
-                let r = r.clamp(0, 0xff) as u8;
-                let g = g.clamp(0, 0xff) as u8;
-                let b = b.clamp(0, 0xff) as u8;
+                stats.primary_rays += 1;
+                let color = self.ray_color(&ray, &mut rand, stats, MAX_DEPTH, false);
 
-                rgb_buff[index * 3] = r;
-                rgb_buff[index * 3 + 1] = g;
-                rgb_buff[index * 3 + 2] = b;
+                if self.mode == RenderMode::Color {
+                    framebuffer.splat(x, y, color.to_fixed(), self.filter);
+                } else {
+                    // Costs only go into the pixel the sample was taken in
+                    let cost = fxi32!((self.mode.cost(stats) - cost) as i32);
+                    framebuffer.splat(x, y, Vec3FI32::from(cost), Filter::Box);
+                }
             }
+        }
+
+        stats.add_row_time(row, stopwatch.elapsed());
+    }
+
+    /// Traces every sample of pixel (`x`, `y`) exactly like a render would, writing every bounce
+    /// to the log. `passes` are the samples the render took, see `RenderReport::passes`, or just
+    /// `0..samples()` for renders that weren't timed.
+    pub fn debug_pixel(&self, x: u16, y: u16, passes: &[Range<u16>]) {
+        let mut stats = RenderStats::default();
+        let mut framebuffer = Framebuffer::new_region(x, y, 1, 1);
+
+        dprintln!("Tracing pixel ({x}, {y}), {} passes", passes.len());
+
+        for samples in passes {
+            for sample in samples.clone() {
+                let (mut rand, sx, sy, ray) = self.camera_sample(x, y, sample, samples);
 
-            if i % 10 == 0 {
-                progress_callback(screen_buff, i);
+                dprintln!("Sample {sample} at ({sx}, {sy})");
+
+                let color = self.ray_color(&ray, &mut rand, &mut stats, MAX_DEPTH, true);
+                framebuffer.add_sample(0, color.to_fixed(), fxi32!(1));
+
+                dprintln!("  color: {color}");
             }
         }
+
+        dprintln!("Pixel average: {}", framebuffer.pixel(0));
+    }
+
+    /// The RNG, position (in pixels from the image's top-left corner) and camera ray of one sample
+    /// of pixel (`x`, `y`).
+    fn camera_sample(
+        &self,
+        x: u16,
+        y: u16,
+        sample: u16,
+        samples: &Range<u16>,
+    ) -> (Rand32, FixedI32, FixedI32, Ray<T>) {
+        let mut rand = sample_rng(self.seed, x, y, sample);
+        let (dx, dy) = filter::stratified_offset(
+            &mut rand,
+            sample - samples.start,
+            samples.end - samples.start,
+        );
+        let (sx, sy) = (dx + x as i32, dy + y as i32);
+        let u = T::from_fixed(sx / self.width as i32);
+        let v = T::from_fixed(sy / self.height as i32);
+
+        let ray = if self.lens_blur {
+            self.camera.get_ray_blur(&mut rand, u, v)
+        } else {
+            self.camera.get_ray_noblur(u, v)
+        };
+
+        (rand, sx, sy, ray)
     }
 
     fn ray_color(
         &self,
-        ray: &Ray,
-        world: &impl Hittable,
+        ray: &Ray<T>,
         rand: &mut Rand32,
+        stats: &mut RenderStats,
         ray_num: u8,
-    ) -> Vec3FI32 {
+        log: bool,
+    ) -> Vec3<T> {
+        let bounce = MAX_DEPTH - ray_num;
+
         if ray_num == 0 {
-            return Vec3FI32::default();
+            if log {
+                dprintln!("  [{bounce}] hit the bounce limit");
+            }
+
+            stats.bounce_limit += 1;
+            return Vec3::default();
         }
 
         let mut rec = HitRecord::default();
 
-        if world.hit(ray, &mut rec, fxi32!(0.001), fxi32!(50)) {
+        if self.scene.hit_with_stats(
+            ray,
+            &mut rec,
+            T::from_fixed(T_MIN),
+            T::from_fixed(T_MAX),
+            Some(&mut *stats),
+        ) {
+            if log {
+                dprintln!(
+                    "  [{bounce}] ray {ray} hit object {} at t = {}, point: {}, normal: {}, front: {}",
+                    rec.object_id,
+                    rec.t,
+                    rec.point,
+                    rec.normal,
+                    rec.front
+                );
+
+                // Easier to picture than the vectors. The heading goes from +z towards +x.
+                let (dir, normal) = (ray.dir().unit_vector().to_fixed(), rec.normal.to_fixed());
+                let degrees = |angle: FixedI32| angle * 180 / FixedI32::PI;
+                dprintln!(
+                    "  [{bounce}] incidence: {:.1} deg, normal heading: {:.1} deg, elevation: {:.1} deg",
+                    degrees((-dir.dot(normal)).acos()),
+                    degrees(normal.x.atan2(normal.z)),
+                    degrees(normal.y.asin())
+                );
+            }
+
             if let Some(ref material) = rec.material {
-                if let Some((new_ray, attenuation)) = material.scatter(rand, ray, &rec) {
-                    return self.ray_color(&new_ray, world, rand, ray_num - 1) * attenuation;
+                stats.scatters += 1;
+
+                let scattered = material.scatter(rand, ray, &rec);
+
+                if log {
+                    dprintln!(
+                        "  [{bounce}] material: {} (albedo {})",
+                        material.name(),
+                        material.albedo(&rec)
+                    );
+
+                    if let Some((ref new_ray, attenuation)) = scattered {
+                        dprintln!("  [{bounce}] scattered: {new_ray}, attenuation: {attenuation}");
+                    } else {
+                        dprintln!("  [{bounce}] absorbed");
+                    }
+                }
+
+                if let Some((new_ray, attenuation)) = scattered {
+                    stats.bounce_rays += 1;
+                    return self.ray_color(&new_ray, rand, stats, ray_num - 1, log) * attenuation;
                 }
+            } else if log {
+                dprintln!("  [{bounce}] no material, absorbed");
             }
 
-            return Vec3FI32::default();
+            stats.absorbed += 1;
+            return Vec3::default();
+        }
+
+        let sky = Self::sky_color(ray);
+
+        if log {
+            dprintln!("  [{bounce}] ray {ray} missed, sky: {sky}");
         }
 
+        sky
+    }
+
+    fn sky_color(ray: &Ray<T>) -> Vec3<T> {
         let unit_dir = ray.dir().unit_vector();
-        let t = (unit_dir.y + 1) * fxi32!(0.5);
+        let t = (unit_dir.y + T::ONE) * T::from_fixed(fxi32!(0.5));
+        let blue = Vec3FI32::new(fxi32!("0.5"), fxi32!("0.7"), fxi32!("1"));
 
-        Vec3FI32::from(fxi32!(1)) * (fxi32!(1) - t)
-            + Vec3FI32::new(fxi32!("0.5"), fxi32!("0.7"), fxi32!("1")) * t
+        Vec3::from(T::ONE) * (T::ONE - t) + Vec3::from_fixed(blue) * t
     }
 }
@@ -0,0 +1,31 @@
+use core::time::Duration;
+
+#[cfg(not(feature = "std"))]
+use ndless::time::SystemTime as Now;
+#[cfg(feature = "std")]
+use std::time::Instant as Now;
+
+/// Measures how long a render has been going. The calculator goes by `SystemTime` (which only
+/// ticks once a second there), the host gets a proper monotonic clock.
+pub struct Stopwatch {
+    start: Now,
+}
+
+impl Stopwatch {
+    /// The smallest step the clock can measure.
+    #[cfg(not(feature = "std"))]
+    pub const RESOLUTION: Duration = Duration::from_secs(1);
+    #[cfg(feature = "std")]
+    pub const RESOLUTION: Duration = Duration::from_millis(1);
+
+    pub fn start() -> Self {
+        Self { start: Now::now() }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        #[cfg(not(feature = "std"))]
+        return self.start.elapsed().unwrap_or_default();
+        #[cfg(feature = "std")]
+        return self.start.elapsed();
+    }
+}
@@ -0,0 +1,188 @@
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use ndless::io::{self, Write};
+use oorandom::Rand32;
+use std::{env, fs, println};
+
+use crate::{
+    caster::{RenderControl, Renderer},
+    clock::Stopwatch,
+    export,
+    filter::Filter,
+    fixed::FixedI32,
+    framebuffer::Framebuffer,
+    scalar::Scalar,
+    screen::{LCD_HEIGHT, LCD_WIDTH},
+    stats::RenderStats,
+    tile::Rect,
+    tonemap::ToneMapper,
+};
+
+/// How far apart two renders of the same scene are on screen, after tone mapping to RGB888. That's
+/// what decides whether a change to the fixed point math is visible at all, the unit tests only
+/// say how far off single operations are.
+pub struct ImageDiff {
+    width: u16,
+    /// Absolute difference of every channel of every pixel.
+    errors: Vec<[u8; 3]>,
+}
+
+impl ImageDiff {
+    pub fn new(reference: &Framebuffer, image: &Framebuffer, tone_mapper: &ToneMapper) -> Self {
+        let len = reference.width() as usize * reference.height() as usize;
+        let errors = (0..len)
+            .map(|i| {
+                let (_, a) = reference.resolve_pixel(i, tone_mapper);
+                let (_, b) = image.resolve_pixel(i, tone_mapper);
+
+                let mut error = [0; 3];
+                for ((e, a), b) in error.iter_mut().zip(a.iter()).zip(b.iter()) {
+                    *e = (*a as i16 - *b as i16).unsigned_abs() as u8;
+                }
+
+                error
+            })
+            .collect();
+
+        Self {
+            width: reference.width(),
+            errors,
+        }
+    }
+
+    /// The largest difference of any channel, and the pixel it's in (to look at with the pixel
+    /// debugger).
+    pub fn max(&self) -> (u8, u16, u16) {
+        let (index, error) = self
+            .errors
+            .iter()
+            .map(|e| e[0].max(e[1]).max(e[2]))
+            .enumerate()
+            .max_by_key(|&(_, error)| error)
+            .unwrap_or((0, 0));
+
+        let width = self.width as usize;
+        (error, (index % width) as u16, (index / width) as u16)
+    }
+
+    /// How many pixels aren't exactly the same.
+    pub fn changed(&self) -> usize {
+        self.errors.iter().filter(|e| **e != [0; 3]).count()
+    }
+
+    pub fn mean(&self) -> f64 {
+        let sum: u64 = self.errors.iter().flatten().map(|&e| e as u64).sum();
+        sum as f64 / (self.errors.len() * 3) as f64
+    }
+
+    /// Peak signal to noise ratio in dB, infinite for identical images. Above 40 or so the
+    /// difference is hard to spot.
+    pub fn psnr(&self) -> f64 {
+        let squares: u64 = self
+            .errors
+            .iter()
+            .flatten()
+            .map(|&e| e as u64 * e as u64)
+            .sum();
+        let mse = squares as f64 / (self.errors.len() * 3) as f64;
+
+        10.0 * (255.0 * 255.0 / mse).log10()
+    }
+
+    /// Writes the differences as a PPM, multiplied by `gain` since they're mostly tiny.
+    pub fn write_ppm<W: Write>(&self, out: &mut W, gain: u8) -> io::Result<()> {
+        let height = self.errors.len() / self.width as usize;
+        export::write_ppm_header(out, self.width as usize, height)?;
+
+        for error in self.errors.iter() {
+            for &e in error.iter() {
+                out.write_all(&[e.saturating_mul(gain)])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ImageDiff {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (max, x, y) = self.max();
+
+        write!(
+            f,
+            "{} of {} pixels differ, mean error {:.3}, max error {max} at ({x}, {y}), PSNR {:.2} dB",
+            self.changed(),
+            self.errors.len(),
+            self.mean(),
+            self.psnr()
+        )
+    }
+}
+
+/// The usual scene at 320x240, on one thread so that both renders take the same path through the
+/// renderer.
+fn render<T: Scalar>(samples: u16) -> Framebuffer {
+    let (width, height) = (LCD_WIDTH as u16, LCD_HEIGHT as u16);
+    let renderer: Renderer<T> = Renderer::new(
+        crate::gen_camera(width, height, false),
+        crate::gen_scene(&mut Rand32::new(2)),
+        width,
+        height,
+        samples,
+        false,
+        ToneMapper::default(),
+        Filter::Mitchell,
+        crate::RENDER_SEED,
+    );
+
+    let mut framebuffer = Framebuffer::new(width, height);
+    renderer.render_region(
+        Rect::new(0, 0, width, height),
+        &mut framebuffer,
+        None,
+        &mut RenderStats::default(),
+        &mut |_, _| RenderControl::Continue,
+    );
+
+    framebuffer
+}
+
+/// `nspray --compare [--samples <n>]` renders the usual scene in fixed point and in f64 with the
+/// same samples, prints how far apart they are and writes the difference to `nspray_diff.ppm`, to
+/// see what a change to `FixedI32` does to an actual image. Returns false if there's no
+/// `--compare`.
+pub fn run_from_args() -> bool {
+    let args: Vec<_> = env::args().collect();
+    if !args.iter().any(|arg| arg == "--compare") {
+        return false;
+    }
+
+    let samples = args
+        .iter()
+        .position(|arg| arg == "--samples")
+        .map(|index| {
+            let value = args.get(index + 1).and_then(|value| value.parse().ok());
+            value.unwrap_or_else(|| panic!("--samples needs a number"))
+        })
+        .unwrap_or(16);
+
+    let stopwatch = Stopwatch::start();
+    let fixed = render::<FixedI32>(samples);
+    let fixed_time = stopwatch.elapsed();
+    let reference = render::<f64>(samples);
+    let diff = ImageDiff::new(&reference, &fixed, &ToneMapper::default());
+
+    println!(
+        "{} samples per pixel, fixed point in {:?}, f64 in {:?}",
+        samples,
+        fixed_time,
+        stopwatch.elapsed() - fixed_time
+    );
+    println!("{diff}");
+
+    let mut image = Vec::new();
+    diff.write_ppm(&mut image, 16).unwrap();
+    fs::write("nspray_diff.ppm", image).expect("Couldn't write nspray_diff.ppm");
+
+    true
+}
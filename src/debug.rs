@@ -28,7 +28,11 @@ macro_rules! dprintln {
 // }
 
 pub fn dprint_str(dstr: &str) {
-    let file = unsafe { LOG_FILE.as_mut() }.unwrap();
+    // Nothing to log to yet, like in tests
+    let file = match unsafe { LOG_FILE.as_mut() } {
+        Some(file) => file,
+        None => return,
+    };
     let duration = unsafe { START_TIME.as_ref().unwrap().clone() };
 
     let msg = format!(
@@ -0,0 +1,134 @@
+use alloc::{vec, vec::Vec};
+
+use crate::{aov::AovBuffers, fixed::FixedI32, vec3::Vec3FI32};
+
+/// B3 spline used by the à-trous transform: 1/16 * [ 1 4 6 4 1 ]
+const KERNEL: [i64; 5] = [1, 4, 6, 4, 1];
+
+// Edge stopping strengths, as 1 / sigma^2. The color one gets 4x stronger every pass since the
+// noise left over after each pass is roughly halved.
+const COLOR_WEIGHT: i64 = 44; // sigma = 0.15
+const DEPTH_WEIGHT: i64 = 100; // sigma = 0.1 (relative to the depth of the center pixel)
+const ALBEDO_WEIGHT: i64 = 100; // sigma = 0.1
+
+/// Edge-avoiding à-trous wavelet filter (Dammertz et al.) for the RGB888 image `rgb`, guided by
+/// the depth, normal and (if present) albedo AOVs collected while rendering. Each pass blurs with
+/// a 5x5 kernel whose taps are spread 2^pass pixels apart, and neighbours that sit across a
+/// geometric or material edge are weighted down, so noise gets smoothed out while edges stay sharp.
+///
+/// Everything is done in integer math. The working buffers are two 8.8 fixed point copies of the
+/// image in u16s, ~450K each at 320x240, so ~900K on top of the framebuffer (~2.4M) and the AOVs
+/// that are still around at that point.
+///
+/// Panics if `aovs` doesn't have depth and normal buffers.
+pub fn denoise(rgb: &mut [u8], width: usize, height: usize, aovs: &AovBuffers, iterations: u8) {
+    let depth = aovs.depth.as_ref().expect("Denoising needs a depth AOV");
+    let normal = aovs.normal.as_ref().expect("Denoising needs a normal AOV");
+    let albedo = aovs.albedo.as_ref();
+
+    assert_eq!(aovs.width() as usize, width);
+    assert_eq!(aovs.height() as usize, height);
+
+    // The 8 fractional bits make these close enough to Q16.16 colors in [0, 1)
+    let mut src: Vec<u16> = rgb[..width * height * 3]
+        .iter()
+        .map(|&c| (c as u16) << 8)
+        .collect();
+    let mut dst = vec![0u16; width * height * 3];
+
+    for pass in 0..iterations {
+        let step = 1isize << pass;
+        let color_weight = COLOR_WEIGHT << (2 * pass);
+
+        for i in 0..height {
+            for j in 0..width {
+                let p = i * width + j;
+
+                // Nothing was hit, so the sky is already noise free
+                if normal[p] == Vec3FI32::default() {
+                    dst[p * 3..p * 3 + 3].copy_from_slice(&src[p * 3..p * 3 + 3]);
+                    continue;
+                }
+
+                let mut sum = [0i64; 3];
+                let mut weight_sum = 0i64;
+
+                for (ky, hy) in KERNEL.iter().enumerate() {
+                    let y = i as isize + (ky as isize - 2) * step;
+                    if y < 0 || y >= height as isize {
+                        continue;
+                    }
+
+                    for (kx, hx) in KERNEL.iter().enumerate() {
+                        let x = j as isize + (kx as isize - 2) * step;
+                        if x < 0 || x >= width as isize {
+                            continue;
+                        }
+
+                        let q = y as usize * width + x as usize;
+
+                        let normal_weight = normal_weight(normal[p], normal[q]);
+                        if normal_weight == 0 {
+                            continue;
+                        }
+
+                        let mut dist = 0i64;
+
+                        for c in 0..3 {
+                            let d = src[q * 3 + c] as i64 - src[p * 3 + c] as i64;
+                            dist += ((d * d) >> 16) * color_weight;
+                        }
+
+                        if depth[p] > FixedI32::default() {
+                            let rel = ((depth[q] - depth[p]) / depth[p]).to_bits() as i64;
+                            dist += ((rel * rel) >> 16) * DEPTH_WEIGHT;
+                        }
+
+                        if let Some(albedo) = albedo {
+                            let d = (albedo[q] - albedo[p]).mag_squared().to_bits() as i64;
+                            dist += d * ALBEDO_WEIGHT;
+                        }
+
+                        let falloff = FixedI32::from_bits(dist.min(i32::MAX as i64) as i32)
+                            .exp_neg()
+                            .to_bits() as i64;
+                        let w = ((falloff * normal_weight) >> 16) * hy * hx;
+
+                        for (c, sum) in sum.iter_mut().enumerate() {
+                            *sum += w * src[q * 3 + c] as i64;
+                        }
+                        weight_sum += w;
+                    }
+                }
+
+                for (c, sum) in sum.iter().enumerate() {
+                    // A weighted average, so it can't get past the brightest neighbour
+                    dst[p * 3 + c] = if weight_sum > 0 {
+                        (sum / weight_sum) as u16
+                    } else {
+                        src[p * 3 + c]
+                    };
+                }
+            }
+        }
+
+        core::mem::swap(&mut src, &mut dst);
+    }
+
+    for (out, c) in rgb.iter_mut().zip(src.iter()) {
+        *out = ((*c as u32 + 0x80) >> 8).min(0xff) as u8;
+    }
+}
+
+/// max(0, n_p . n_q)^8 in Q16.16
+fn normal_weight(np: Vec3FI32, nq: Vec3FI32) -> i64 {
+    let dot = np.dot(nq);
+
+    if dot <= FixedI32::default() {
+        return 0;
+    }
+
+    let dot = dot * dot;
+    let dot = dot * dot;
+    (dot * dot).to_bits() as i64
+}
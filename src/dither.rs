@@ -1,26 +1,27 @@
-use crate::{dprintln, IMG_HEIGHT, IMG_WIDTH};
+use crate::dprintln;
 use alloc::vec;
 
-/// Dithers the input array (RGB888) and puts the output into the output array (RGB555).
+/// Dithers the input array (RGB888) and puts the output into the output array (RGB555), both
+/// `width` x `height` pixels.
 /// This uses the small kernel made by Floyd and Steinberg:
 /// 1/16 * [ _ # 7 ]
 ///        [ 3 5 1 ]
-pub fn dither(in_img: &[u8], out_img: &mut [u16]) {
+pub fn dither(in_img: &[u8], out_img: &mut [u16], width: usize, height: usize) {
     // let kernel = [[0i16, 0, 0, 7, 5], [3i16, 5, 7, 5, 3], [1i16, 3, 5, 3, 1]];
     let kernel = [[0i16, 0, 7], [3i16, 5, 1]];
 
-    for i in 0..(IMG_HEIGHT * IMG_WIDTH) {
+    for i in 0..(height * width) {
         out_img[i] = 0;
     }
 
     // 0 -> R, 1 -> G, 2 -> B
     for c in 0..3 {
-        let mut error_buff = vec![0i16; IMG_WIDTH * IMG_HEIGHT * 3];
+        let mut error_buff = vec![0i16; width * height * 3];
 
-        for i in 0..IMG_HEIGHT {
-            for j in 0..IMG_WIDTH {
-                let in_index = (i * IMG_WIDTH + j) * 3;
-                let out_index = i * IMG_WIDTH + j;
+        for i in 0..height {
+            for j in 0..width {
+                let in_index = (i * width + j) * 3;
+                let out_index = i * width + j;
 
                 let color = in_img[in_index + c] as i16;
                 let converted_color =
@@ -35,15 +36,15 @@ pub fn dither(in_img: &[u8], out_img: &mut [u16]) {
                             continue;
                         }
 
-                        if k + i >= IMG_HEIGHT {
+                        if k + i >= height {
                             continue;
                         }
 
-                        if (j as isize + n as isize - 1) < 0 || n + j >= IMG_WIDTH {
+                        if (j as isize + n as isize - 1) < 0 || n + j >= width {
                             continue;
                         }
 
-                        error_buff[out_index + k * IMG_WIDTH + n - 1] -= error * kernel[k][n] / 16;
+                        error_buff[out_index + k * width + n - 1] -= error * kernel[k][n] / 16;
                     }
 
                     // for k in 0..3 {
@@ -52,15 +53,15 @@ pub fn dither(in_img: &[u8], out_img: &mut [u16]) {
                     //             continue;
                     //         }
 
-                    //         if k + i >= IMG_HEIGHT {
+                    //         if k + i >= height {
                     //             continue;
                     //         }
 
-                    //         if (j as isize + n as isize - 2) < 0 || n + j >= IMG_WIDTH {
+                    //         if (j as isize + n as isize - 2) < 0 || n + j >= width {
                     //             continue;
                     //         }
 
-                    //         error_buff[out_index + k * IMG_WIDTH + n - 2] -= error * kernel[k][n] / 48;
+                    //         error_buff[out_index + k * width + n - 2] -= error * kernel[k][n] / 48;
                     //     }
                 }
             }
@@ -0,0 +1,60 @@
+use crate::fixed::Fixed;
+
+/// Fractional bits of the exponents worked with internally.
+const EXP_FRAC: u32 = 30;
+
+/// 2^(2^-i) for i = 1 to 30, in Q2.30.
+const EXP2_STEPS: [u64; 30] = [
+    1518500250, 1276901417, 1170923762, 1121280436, 1097253708, 1085434106, 1079572136, 1076653033,
+    1075196443, 1074468888, 1074105294, 1073923544, 1073832680, 1073787251, 1073764537, 1073753181,
+    1073747502, 1073744663, 1073743244, 1073742534, 1073742179, 1073742001, 1073741913, 1073741868,
+    1073741846, 1073741835, 1073741830, 1073741827, 1073741825, 1073741825,
+];
+
+/// log2(e) in Q2.30.
+const LOG2_E: i64 = 1549082005;
+
+/// 2^`x` for a Q30 `x`, as the raw bits of a number with `frac` fractional bits. Saturates if it
+/// doesn't fit and goes to 0 if it's too small.
+fn exp2_bits(x: i64, frac: u32) -> i32 {
+    let whole = x >> EXP_FRAC;
+    let fraction = x & ((1 << EXP_FRAC) - 1);
+
+    // 2^fraction, one bit at a time, in Q2.30
+    let mut result = 1u64 << EXP_FRAC;
+    for (i, step) in EXP2_STEPS.iter().enumerate() {
+        if fraction & (1 << (EXP_FRAC as usize - 1 - i)) != 0 {
+            result = (result * step) >> EXP_FRAC;
+        }
+    }
+
+    let shift = whole + frac as i64 - EXP_FRAC as i64;
+
+    if shift > 0 {
+        // The result is at least 2^30 already
+        i32::MAX
+    } else if shift == 0 {
+        result.min(i32::MAX as u64) as i32
+    } else if shift < -32 {
+        0
+    } else {
+        let shift = -shift;
+        ((result + (1 << (shift - 1))) >> shift) as i32
+    }
+}
+
+impl<const FRAC: u32> Fixed<FRAC> {
+    /// 2^self, off by less than 2^-26 relative before rounding. Saturates when the result doesn't
+    /// fit.
+    pub fn exp2(self) -> Self {
+        Self::from_bits(exp2_bits(
+            (self.to_bits() as i64) << (EXP_FRAC - FRAC),
+            FRAC,
+        ))
+    }
+
+    /// e^self, same accuracy and saturation as `exp2`.
+    pub fn exp(self) -> Self {
+        Self::from_bits(exp2_bits((self.to_bits() as i64 * LOG2_E) >> FRAC, FRAC))
+    }
+}
@@ -0,0 +1,62 @@
+use alloc::format;
+use ndless::io::{self, Write};
+
+use crate::{fixed::FixedI32, vec3::Vec3FI32};
+
+pub fn vec3_channel(v: Vec3FI32, channel: usize) -> FixedI32 {
+    match channel {
+        0 => v.x,
+        1 => v.y,
+        _ => v.z,
+    }
+}
+
+/// Writes a little endian PFM image, the usual format for HDR color and AOVs.
+/// `sample(index, channel)` gives the value of a channel of the pixel at `index` (top-left first).
+/// PFM stores rows bottom to top.
+pub fn write_pfm<W, F>(
+    out: &mut W,
+    width: usize,
+    height: usize,
+    channels: usize,
+    sample: F,
+) -> io::Result<()>
+where
+    W: Write,
+    F: Fn(usize, usize) -> FixedI32,
+{
+    let magic = if channels == 1 { "Pf" } else { "PF" };
+    out.write_all(format!("{magic}\n{width} {height}\n-1.0\n").as_bytes())?;
+
+    for i in (0..height).rev() {
+        for j in 0..width {
+            for c in 0..channels {
+                out.write_all(&sample(i * width + j, c).to_f32().to_le_bytes())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a binary 16-bit greyscale PGM (big endian, as the format requires).
+pub fn write_pgm16<W: Write>(
+    out: &mut W,
+    width: usize,
+    height: usize,
+    data: &[u16],
+) -> io::Result<()> {
+    out.write_all(format!("P5\n{width} {height}\n65535\n").as_bytes())?;
+
+    for value in data.iter().take(width * height) {
+        out.write_all(&value.to_be_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Writes the header of a binary 8-bit RGB PPM, the pixels (RGB888, top row first) follow it
+/// directly so they can be streamed out as they get rendered.
+pub fn write_ppm_header<W: Write>(out: &mut W, width: usize, height: usize) -> io::Result<()> {
+    out.write_all(format!("P6\n{width} {height}\n255\n").as_bytes())
+}
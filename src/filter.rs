@@ -0,0 +1,80 @@
+use oorandom::Rand32;
+
+use crate::{fixed::FixedI32, fxi32};
+
+/// Pixel reconstruction filters. Every sample gets splatted onto all the pixels within the
+/// filter's radius, weighted by the filter evaluated at the distance (in pixels) between the sample
+/// and the pixel's center.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    /// Plain average of the samples inside the pixel.
+    Box,
+    /// Triangle with a radius of one pixel.
+    Tent,
+    /// Truncated gaussian, sigma = 0.5 pixels, radius of 1.5 pixels.
+    Gaussian,
+    /// Mitchell-Netravali with B = C = 1/3, radius of 2 pixels. Sharper than the gaussian, but has
+    /// negative lobes that can ring a little around hard edges.
+    Mitchell,
+}
+
+impl Filter {
+    pub fn radius(self) -> FixedI32 {
+        match self {
+            Filter::Box => fxi32!(0.5),
+            Filter::Tent => fxi32!(1),
+            Filter::Gaussian => fxi32!(1.5),
+            Filter::Mitchell => fxi32!(2),
+        }
+    }
+
+    /// Weight of a sample that's `dx`, `dy` pixels away from the center of a pixel.
+    pub fn weight(self, dx: FixedI32, dy: FixedI32) -> FixedI32 {
+        // All of these are separable
+        self.weight_1d(dx.abs()) * self.weight_1d(dy.abs())
+    }
+
+    fn weight_1d(self, x: FixedI32) -> FixedI32 {
+        if x >= self.radius() {
+            return fxi32!(0);
+        }
+
+        match self {
+            Filter::Box => fxi32!(1),
+            Filter::Tent => fxi32!(1) - x,
+            Filter::Gaussian => {
+                // exp(-x^2 / (2 sigma^2)), shifted down so it reaches 0 right at the radius
+                let edge = (-self.radius() * self.radius() * 2).exp();
+                ((-x * x * 2).exp() - edge).max(fxi32!(0))
+            }
+            Filter::Mitchell => {
+                let x2 = x * x;
+                let x3 = x2 * x;
+
+                if x < fxi32!(1) {
+                    (x3 * 7 - x2 * 12 + fxi32!(16) / 3) / 6
+                } else {
+                    (-x3 * 7 / 3 + x2 * 12 - x * 20 + fxi32!(32) / 3) / 6
+                }
+            }
+        }
+    }
+}
+
+/// Stratified sample positions inside a pixel: the pixel is split into an n x n grid (n being the
+/// largest whole square root of the sample count) and every sample gets a random spot in the next
+/// cell. Returns the offset from the pixel's top-left corner, both in [0, 1).
+pub fn stratified_offset(rand: &mut Rand32, sample: u16, samples: u16) -> (FixedI32, FixedI32) {
+    let mut n = 1;
+    while (n + 1) * (n + 1) <= samples as i32 {
+        n += 1;
+    }
+
+    let cell = sample as i32 % (n * n);
+    let (cx, cy) = (cell % n, cell / n);
+
+    (
+        (FixedI32::rand(rand) + cx) / n,
+        (FixedI32::rand(rand) + cy) / n,
+    )
+}
@@ -4,58 +4,143 @@
 
 use core::{
     cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd},
+    convert::TryFrom,
     fmt::{self, Display, Formatter},
     ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+    str::FromStr,
 };
 
-use alloc::{
-    format,
-    string::{String, ToString},
-};
+use alloc::{format, string::String};
 use ndless::prelude::Float;
 use oorandom::Rand32;
 
-const PREC: i32 = 16;
+use crate::overflow_check;
+
+/// exp(-x) for x = 0, 0.25, 0.5, ..., 8, linearly interpolated in between by `exp_neg`.
+const EXP_NEG: [i32; 33] = [
+    65536, 51039, 39750, 30957, 24109, 18776, 14623, 11388, 8869, 6907, 5380, 4190, 3263, 2541,
+    1979, 1541, 1200, 935, 728, 567, 442, 344, 268, 209, 162, 127, 99, 77, 60, 47, 36, 28, 22,
+];
+
+/// 1/sqrt(m) in Q2.30 for the middle of each 1/8 wide step of m in [1, 4). Gets `rsqrt` to within
+/// about 3% before its newton steps.
+const RSQRT_SEED: [u32; 24] = [
+    1041682578, 985333074, 937238702, 895562589, 858993459, 826566842, 797555404, 771398898,
+    747657839, 725981977, 706088274, 687745184, 670761200, 654976372, 640255922, 626485368,
+    613566757, 601415717, 589959130, 579133272, 568882316, 559157115, 549914212, 541115017,
+];
 
-pub const PI: FixedI32 = FixedI32::from_dec(3, 14159, 5);
+/// Integer square root, rounded to nearest, worked out one bit at a time.
+pub fn isqrt(n: u64) -> u64 {
+    let mut rem = n;
+    let mut root = 0u64;
+    let mut bit = 1u64 << 62;
+
+    while bit > rem {
+        bit >>= 2;
+    }
+
+    while bit != 0 {
+        if rem >= root + bit {
+            rem -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+        bit >>= 2;
+    }
 
+    // rem = n - root^2, and (root + 0.5)^2 = root^2 + root + 0.25
+    if rem > root {
+        root += 1;
+    }
+
+    root
+}
+
+/// Q16.16, the format the renderer does all of its math in.
+pub type FixedI32 = Fixed<16>;
+
+// From before pi moved into Fixed, still here for anything that uses it
+#[allow(dead_code)]
+pub const PI: FixedI32 = FixedI32::PI;
+
+/// A FixedI32 from anything it converts from. Number and string literals get parsed at compile
+/// time, so they cost nothing, work in consts and statics and don't build if they're invalid or
+/// out of range. Anything else gets converted at runtime.
 #[macro_export]
 macro_rules! fxi32 {
+    (- $element:literal) => {{
+        const VALUE: $crate::fixed::FixedI32 =
+            $crate::fixed::FixedI32::from_literal(stringify!($element), true);
+        VALUE
+    }};
+    ($element:literal) => {{
+        const VALUE: $crate::fixed::FixedI32 =
+            $crate::fixed::FixedI32::from_literal(stringify!($element), false);
+        VALUE
+    }};
     ($element:expr) => {
         $crate::fixed::FixedI32::from($element)
     };
 }
 
-/// A fixed point encoded i32 that has 16 bits of percision:
+/// A fixed point encoded i32 with `FRAC` bits of percision. For FRAC = 16:
 /// 0 1 0 1 0 1 0 0 0 0 0 1 0 0 0 0 . 1 0 1 0 1 0 0 0 0 0 1 0 0 0 0 0
 /// = 86082 + 321/512
 /// This is neccessary (i think), because the TI Nspire doesn't have a native FPU. Or atleast, I
 /// think, not that I can check because the people at TI are all a bunch of fucking prudes.
+///
+/// FRAC has to be between 2 and 30.
 #[derive(Clone, Copy, Debug)]
-pub struct FixedI32 {
+pub struct Fixed<const FRAC: u32> {
     value: i32,
 }
 
-impl FixedI32 {
+/// pi in Q3.29, which is as many bits of it as fit in an i32.
+const PI_Q29: i64 = 0x6487_ed51;
+
+impl<const FRAC: u32> Fixed<FRAC> {
+    /// pi, rounded to nearest. With 30 fractional bits it doesn't fit anymore and saturates to the
+    /// largest number there is.
+    pub const PI: Self = Self::from_bits(if FRAC > 29 {
+        i32::MAX
+    } else {
+        (((PI_Q29 << FRAC >> 28) + 1) >> 1) as i32
+    });
+
+    // Like `pow` and `clamp`, nothing calls this anymore, but it's been part of the API forever
+    #[allow(dead_code)]
     pub fn new(value: i32) -> Self {
         Self {
-            value: value << PREC,
+            value: value << FRAC,
         }
     }
 
+    /// `frac` is the raw fractional bits, so it only really makes sense for Q16.16.
     pub const fn from_components(whole: i16, frac: u16) -> Self {
         Self {
-            value: ((whole as i32) << PREC) | frac as i32,
+            value: ((whole as i32) << FRAC) | frac as i32,
         }
     }
 
     pub const fn from_dec(whole: i16, frac: u16, frac_mag: u8) -> Self {
         Self {
-            value: ((whole as i32) << PREC)
-                | (((frac as i32) << PREC) / 10i32.pow(frac_mag as u32)),
+            value: ((whole as i32) << FRAC)
+                | (((frac as i64) << FRAC) / 10i64.pow(frac_mag as u32)) as i32,
         }
     }
 
+    /// Builds a value straight from its raw representation.
+    pub const fn from_bits(value: i32) -> Self {
+        Self { value }
+    }
+
+    /// The raw representation, for code that needs to do its own integer math.
+    pub const fn to_bits(self) -> i32 {
+        self.value
+    }
+
     pub fn modulo(self, modulus: Self) -> Self {
         Self {
             value: self.value % modulus.value,
@@ -68,35 +153,119 @@ impl FixedI32 {
         }
     }
 
-    /// Reasonably accurate tangent approximation.
-    pub fn tan(self) -> Self {
-        self.sin() / self.cos()
+    /// Table based exp(-x) for x >= 0, good to about 3 decimal places. Anything past 8 is
+    /// treated as 0.
+    pub fn exp_neg(self) -> Self {
+        let x = self.value.max(0);
+        // Quarter steps
+        let index = (x >> (FRAC - 2)) as usize;
+
+        if index >= EXP_NEG.len() - 1 {
+            return Self { value: 0 };
+        }
+
+        let frac = (x & ((1 << (FRAC - 2)) - 1)) as i64;
+        let (lo, hi) = (
+            Self::from_q16(EXP_NEG[index]) as i64,
+            Self::from_q16(EXP_NEG[index + 1]) as i64,
+        );
+
+        Self {
+            value: (lo + (((hi - lo) * frac) >> (FRAC - 2))) as i32,
+        }
+    }
+
+    /// Rescales a raw Q16.16 value (like the table entries) to this format.
+    fn from_q16(value: i32) -> i32 {
+        if FRAC >= 16 {
+            value << (FRAC - 16)
+        } else {
+            value >> (16 - FRAC)
+        }
+    }
+
+    /// `self + rhs`, or `None` if it overflows.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.value.checked_add(rhs.value).map(Self::from_bits)
     }
 
-    /// Reasonably accurate sine approximation.
-    pub fn sin(self) -> Self {
-        let mut sign = self.value.signum();
-        let mut r = (self * sign).modulo(PI * 2);
+    /// `self - rhs`, or `None` if it overflows.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.value.checked_sub(rhs.value).map(Self::from_bits)
+    }
 
-        if r >= PI {
-            sign *= -1;
-            r -= PI;
+    /// `self * rhs`, or `None` if it overflows.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let res = (self.value as i64 * rhs.value as i64) >> FRAC;
+        i32::try_from(res).ok().map(Self::from_bits)
+    }
+
+    /// `self / rhs`, or `None` if it overflows or `rhs` is 0.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.value == 0 {
+            return None;
         }
 
-        if r >= PI / 2 {
-            r = PI - r;
+        let res = ((self.value as i64) << FRAC) / rhs.value as i64;
+        i32::try_from(res).ok().map(Self::from_bits)
+    }
+
+    /// `self * rhs` for a whole number `rhs`, or `None` if it overflows.
+    pub fn checked_mul_int(self, rhs: i32) -> Option<Self> {
+        self.value.checked_mul(rhs).map(Self::from_bits)
+    }
+
+    /// `self / rhs` for a whole number `rhs`, or `None` if it overflows or `rhs` is 0.
+    pub fn checked_div_int(self, rhs: i32) -> Option<Self> {
+        self.value.checked_div(rhs).map(Self::from_bits)
+    }
+
+    /// `value` as a fixed point number, or `None` if it doesn't fit.
+    pub fn checked_from_int(value: i32) -> Option<Self> {
+        value.checked_mul(1 << FRAC).map(Self::from_bits)
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        self.value.checked_neg().map(Self::from_bits)
+    }
+
+    // Only the tests call these three for now
+    #[allow(dead_code)]
+    pub fn saturating_add(self, rhs: Self) -> Self {
+        Self::from_bits(self.value.saturating_add(rhs.value))
+    }
+
+    #[allow(dead_code)]
+    pub fn saturating_sub(self, rhs: Self) -> Self {
+        Self::from_bits(self.value.saturating_sub(rhs.value))
+    }
+
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        Self::saturate((self.value as i64 * rhs.value as i64) >> FRAC)
+    }
+
+    /// `self / rhs`, clamped to the representable range. Dividing by 0 gives the largest number
+    /// with the sign of `self` (or 0 for 0 / 0) instead of panicking.
+    pub fn saturating_div(self, rhs: Self) -> Self {
+        if rhs.value == 0 {
+            return Self::saturate(self.value.signum() as i64 * i64::MAX);
         }
 
-        r - r.pow(3) / 6 + r.pow(5) / 120 - r.pow(7) / 5040 * sign
+        Self::saturate(((self.value as i64) << FRAC) / rhs.value as i64)
+    }
+
+    #[allow(dead_code)]
+    pub fn saturating_mul_int(self, rhs: i32) -> Self {
+        Self::from_bits(self.value.saturating_mul(rhs))
     }
 
-    /// Reasonably accurate cosine approximation.
-    pub fn cos(self) -> Self {
-        (PI / 2 + self).sin()
+    fn saturate(value: i64) -> Self {
+        Self::from_bits(value.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
     }
 
+    #[allow(dead_code)]
     pub fn pow(self, n: u32) -> Self {
-        let mut accum = FixedI32::from(1);
+        let mut accum = Self::from(1);
 
         for _ in 0..n {
             accum *= self;
@@ -105,158 +274,449 @@ impl FixedI32 {
         accum
     }
 
+    /// Square root, rounded to the nearest representable number (so off by at most half a step).
+    /// Negative numbers give 0.
     pub fn sqrt(self) -> Self {
-        // Just a simple bit of newton's method, quite slow.
-        let a_half = self / 2;
-        let mut x_n = FixedI32::from(1);
+        if self.value <= 0 {
+            return Self::from_bits(0);
+        }
+
+        // sqrt(v / 2^FRAC) * 2^FRAC = sqrt(v * 2^FRAC)
+        Self::from_bits(isqrt((self.value as u64) << FRAC) as i32)
+    }
 
-        for _ in 0..10 {
-            x_n = x_n / 2 + a_half / x_n;
+    /// 1 / sqrt(self). The relative error is below 2^-18 before rounding to the nearest step, so
+    /// for results around 1 in Q16.16 it's off by at most 0.75 of a step. Zero and negative numbers
+    /// give the largest representable number, as does anything too small for the result to fit.
+    pub fn rsqrt(self) -> Self {
+        if self.value <= 0 {
+            return Self::from_bits(i32::MAX);
         }
 
-        x_n
+        // Normalize to m = v * 2^s in [2^30, 2^32), with s the same parity as FRAC so that the
+        // exponent of self = (m / 2^30) * 2^(30 - s - FRAC) can be halved. v is positive, so
+        // there's always at least one leading zero to spare.
+        let v = self.value as u32;
+        let zeros = v.leading_zeros() as i32;
+        let s = if (zeros - FRAC as i32) % 2 == 0 {
+            zeros
+        } else {
+            zeros - 1
+        };
+        let m = (v << s) as u64;
+
+        // Two newton steps on y = 1 / sqrt(m / 2^30) in Q2.30: y = y * (3 - m * y^2) / 2
+        let mut y = RSQRT_SEED[(m >> 27) as usize - 8] as u64;
+        for _ in 0..2 {
+            let y2 = (y * y) >> 30;
+            let t = (3 << 30) - ((m * y2) >> 30);
+            y = (y * t) >> 31;
+        }
+
+        // self^-1/2 = y * 2^-((30 - s - FRAC) / 2), and the result wants FRAC fractional bits
+        let shift = 30 - FRAC as i32 + (30 - s - FRAC as i32) / 2;
+        let bits = if shift >= 0 {
+            (y + (1 << shift >> 1)) >> shift
+        } else {
+            y.checked_shl(-shift as u32)
+                .filter(|bits| bits >> -shift == y)
+                .unwrap_or(u64::MAX)
+        };
+
+        Self::from_bits(bits.min(i32::MAX as u64) as i32)
     }
 
+    #[allow(dead_code)]
     pub fn clamp(self, begin: i32, end: i32) -> Self {
-        let w = self.value >> PREC;
+        let w = self.value >> FRAC;
 
         if w <= begin {
             Self {
-                value: begin << PREC,
+                value: begin << FRAC,
             }
         } else if w >= end {
-            Self { value: end << PREC }
+            Self { value: end << FRAC }
         } else {
             self
         }
     }
 
-    /// Generates a new FixedI32 in the range of [0, 1)
+    /// Lossy conversion for exporting values to tools that want floats. Goes through soft-float on
+    /// the calculator, so keep it out of the render loop.
+    pub fn to_f32(self) -> f32 {
+        self.value as f32 / (1 << FRAC) as f32
+    }
+
+    /// Generates a new value in the range of [0, 1)
     pub fn rand(rng: &mut Rand32) -> Self {
         Self {
-            value: (rng.rand_u32() & 0xffff) as i32,
+            value: (rng.rand_u32() & ((1 << FRAC) - 1)) as i32,
         }
     }
 }
 
-impl Into<i32> for FixedI32 {
+impl<const FRAC: u32> Into<i32> for Fixed<FRAC> {
     fn into(self) -> i32 {
-        self.value >> PREC
+        self.value >> FRAC
     }
 }
 
-impl From<&str> for FixedI32 {
-    fn from(mut val: &str) -> Self {
-        let (mut frac, mut frac_mag) = (0, 0);
+/// Why a string couldn't be parsed as a fixed point number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseFixedError {
+    /// No digits at all.
+    Empty,
+    /// Something that isn't a digit, sign, point or exponent, or one of those in the wrong spot.
+    InvalidDigit,
+    /// The number doesn't fit in the format.
+    OutOfRange,
+}
+
+impl Display for ParseFixedError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ParseFixedError::Empty => "no digits in fixed point number",
+            ParseFixedError::InvalidDigit => "invalid digit in fixed point number",
+            ParseFixedError::OutOfRange => "fixed point number out of range",
+        })
+    }
+}
+
+/// Decimal digits kept while parsing. Any more only move the exponent (or get dropped after the
+/// point), which is far below what even 30 fractional bits can resolve.
+const PARSE_DIGITS: u32 = 27;
+
+/// Parses things like "-12", "0.5", ".25", "1.5e-3" or "+2E4" (in `text[start..end]`) into the
+/// raw bits of a number with `frac` fractional bits, rounding to the nearest representable one.
+/// A const fn, so `fxi32!` can do this at compile time, hence all the while loops and indices.
+const fn parse_signed(
+    text: &[u8],
+    start: usize,
+    end: usize,
+    frac: u32,
+) -> Result<i32, ParseFixedError> {
+    let negative = start < end && text[start] == b'-';
+
+    if start < end && (text[start] == b'-' || text[start] == b'+') {
+        parse_bits(text, start + 1, end, negative, frac)
+    } else {
+        parse_bits(text, start, end, negative, frac)
+    }
+}
+
+/// `parse_signed` after the sign.
+const fn parse_bits(
+    text: &[u8],
+    start: usize,
+    end: usize,
+    negative: bool,
+    frac: u32,
+) -> Result<i32, ParseFixedError> {
+    let mut i = start;
+
+    // All the digits as one integer, and the power of ten to scale it by
+    let (mut digits, mut kept, mut scale) = (0u128, 0, 0i32);
+    let mut seen_digit = false;
+    let mut seen_point = false;
+
+    while i < end && text[i] != b'e' && text[i] != b'E' {
+        let c = text[i];
+
+        if c >= b'0' && c <= b'9' {
+            seen_digit = true;
+
+            if kept < PARSE_DIGITS {
+                digits = digits * 10 + (c - b'0') as u128;
+                // Leading zeros don't count towards the limit
+                if digits != 0 {
+                    kept += 1;
+                }
+                if seen_point {
+                    scale -= 1;
+                }
+            } else if !seen_point {
+                scale += 1;
+            }
+        } else if c == b'.' && !seen_point {
+            seen_point = true;
+        } else {
+            return Err(ParseFixedError::InvalidDigit);
+        }
 
-        if let Some(index) = val.find('.') {
-            if index != val.len() - 1 {
-                frac = val[(index + 1)..].parse().unwrap();
-                frac_mag = val.len() - index - 1;
+        i += 1;
+    }
+
+    if !seen_digit {
+        return Err(if i == end && !seen_point {
+            ParseFixedError::Empty
+        } else {
+            ParseFixedError::InvalidDigit
+        });
+    }
+
+    if i < end {
+        // Skip the e
+        i += 1;
+
+        let exponent_negative = i < end && text[i] == b'-';
+        if i < end && (text[i] == b'-' || text[i] == b'+') {
+            i += 1;
+        }
+
+        if i == end {
+            return Err(ParseFixedError::InvalidDigit);
+        }
+
+        let mut exponent = 0i32;
+        while i < end {
+            let c = text[i];
+            if c < b'0' || c > b'9' {
+                return Err(ParseFixedError::InvalidDigit);
             }
 
-            val = &val[0..index];
+            // Way past anything that fits or doesn't round to 0
+            if exponent < 1000 {
+                exponent = exponent * 10 + (c - b'0') as i32;
+            }
+            i += 1;
         }
 
-        let frac = (2 << (PREC - 1)) * frac / 10u32.pow(frac_mag as u32);
-        let value = (val.parse::<i32>().unwrap() << PREC) | frac as i32;
+        scale += if exponent_negative {
+            -exponent
+        } else {
+            exponent
+        };
+    }
 
-        Self { value }
+    // Largest magnitude that fits, -2^31 for negative numbers
+    let limit = if negative {
+        1u128 << 31
+    } else {
+        i32::MAX as u128
+    };
+
+    // digits * 10^scale * 2^frac, rounded to nearest
+    let mut magnitude = digits << frac;
+    if digits == 0 {
+        magnitude = 0;
+    } else if scale >= 0 {
+        while scale > 0 {
+            magnitude = match magnitude.checked_mul(10) {
+                Some(magnitude) if magnitude <= limit => magnitude,
+                _ => return Err(ParseFixedError::OutOfRange),
+            };
+            scale -= 1;
+        }
+    } else if scale < -38 {
+        // digits < 10^27, so it's less than 10^-11 and rounds to 0
+        magnitude = 0;
+    } else {
+        let divisor = 10u128.pow(-scale as u32);
+        magnitude = (magnitude + divisor / 2) / divisor;
+    }
+
+    if magnitude > limit {
+        return Err(ParseFixedError::OutOfRange);
+    }
+
+    let magnitude = magnitude as i64;
+    Ok((if negative { -magnitude } else { magnitude }) as i32)
+}
+
+impl<const FRAC: u32> Fixed<FRAC> {
+    /// Parses the literals in `fxi32!`, which come in as stringified tokens, maybe in quotes. The
+    /// macro takes the minus sign off number literals itself and says so with `negative`. Invalid
+    /// or out of range literals stop the build.
+    pub const fn from_literal(text: &str, negative: bool) -> Self {
+        let text = text.as_bytes();
+        let (mut start, mut end) = (0, text.len());
+
+        if end >= 2 && text[0] == b'"' && text[end - 1] == b'"' {
+            start += 1;
+            end -= 1;
+        }
+
+        let bits = if negative {
+            parse_bits(text, start, end, true, FRAC)
+        } else {
+            parse_signed(text, start, end, FRAC)
+        };
+
+        match bits {
+            Ok(bits) => Self::from_bits(bits),
+            Err(_) => Self::from_bits(invalid_literal()),
+        }
     }
 }
 
-impl From<f32> for FixedI32 {
+/// Makes const evaluation (and so the build) fail on a bad `fxi32!` literal, with a note pointing
+/// at it. Also works on compilers too old for panicking in const fns.
+#[allow(unconditional_panic)]
+const fn invalid_literal() -> i32 {
+    let invalid_fixed_point_literal: [i32; 0] = [];
+    #[allow(clippy::out_of_bounds_indexing)]
+    invalid_fixed_point_literal[0]
+}
+
+impl<const FRAC: u32> FromStr for Fixed<FRAC> {
+    type Err = ParseFixedError;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        parse_signed(val.as_bytes(), 0, val.len(), FRAC).map(Self::from_bits)
+    }
+}
+
+/// Panics on anything `FromStr` doesn't take, which is fine for literals.
+impl<const FRAC: u32> From<&str> for Fixed<FRAC> {
+    fn from(val: &str) -> Self {
+        val.parse().expect("Invalid fixed point literal")
+    }
+}
+
+impl<const FRAC: u32> From<f32> for Fixed<FRAC> {
     fn from(val: f32) -> Self {
         Self {
             value: val.signum() as i32
-                * ((val.abs() as i32) << PREC | ((val.fract().abs() * 2f32.powi(PREC)) as i32)),
+                * ((val.abs() as i32) << FRAC
+                    | ((val.fract().abs() * 2f32.powi(FRAC as i32)) as i32)),
         }
     }
 }
 
-impl From<i64> for FixedI32 {
+impl<const FRAC: u32> From<i64> for Fixed<FRAC> {
     fn from(value: i64) -> Self {
         Self {
-            value: (value as i32) << PREC,
+            value: (value as i32) << FRAC,
         }
     }
 }
 
-impl From<u64> for FixedI32 {
+impl<const FRAC: u32> From<u64> for Fixed<FRAC> {
     fn from(value: u64) -> Self {
         Self {
-            value: (value as i32) << PREC,
+            value: (value as i32) << FRAC,
         }
     }
 }
 
-impl From<i32> for FixedI32 {
+impl<const FRAC: u32> From<i32> for Fixed<FRAC> {
     fn from(value: i32) -> Self {
         Self {
-            value: value << PREC,
+            value: value << FRAC,
         }
     }
 }
 
-impl From<u32> for FixedI32 {
+impl<const FRAC: u32> From<u32> for Fixed<FRAC> {
     fn from(value: u32) -> Self {
         Self {
-            value: (value as i32) << PREC,
+            value: (value as i32) << FRAC,
         }
     }
 }
 
-impl From<i16> for FixedI32 {
+impl<const FRAC: u32> From<i16> for Fixed<FRAC> {
     fn from(value: i16) -> Self {
         Self {
-            value: (value as i32) << PREC,
+            value: (value as i32) << FRAC,
         }
     }
 }
 
-impl From<u16> for FixedI32 {
+impl<const FRAC: u32> From<u16> for Fixed<FRAC> {
     fn from(value: u16) -> Self {
         Self {
-            value: (value as i32) << PREC,
+            value: (value as i32) << FRAC,
         }
     }
 }
 
-impl From<i8> for FixedI32 {
+impl<const FRAC: u32> From<i8> for Fixed<FRAC> {
     fn from(value: i8) -> Self {
         Self {
-            value: (value as i32) << PREC,
+            value: (value as i32) << FRAC,
         }
     }
 }
 
-impl From<u8> for FixedI32 {
+impl<const FRAC: u32> From<u8> for Fixed<FRAC> {
     fn from(value: u8) -> Self {
         Self {
-            value: (value as i32) << PREC,
+            value: (value as i32) << FRAC,
         }
     }
 }
 
-impl Display for FixedI32 {
-    // cheating lol
+/// Prints 5 decimal places (or none for whole numbers) unless asked for a precision, like
+/// `{:.2}`. Rounds to nearest, all in integer math. Width and fill work like they do for integers.
+impl<const FRAC: u32> Display for Fixed<FRAC> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let frac = self.value.abs() & 0xffff;
-        let mut frac_str = String::new();
+        fmt_fixed(
+            f,
+            self.value < 0,
+            (self.value as i64).unsigned_abs(),
+            FRAC,
+            5,
+        )
+    }
+}
+
+/// Does the formatting for the `Display` of any fixed point number given its sign, magnitude and
+/// fractional bits. `magnitude` times 10 has to fit in a u64 once the whole part is masked off.
+pub fn fmt_fixed(
+    f: &mut Formatter,
+    negative: bool,
+    magnitude: u64,
+    frac_bits: u32,
+    default_places: usize,
+) -> fmt::Result {
+    let mask = (1u64 << frac_bits) - 1;
+
+    let places = match f.precision() {
+        Some(places) => places,
+        None if magnitude & mask == 0 => 0,
+        None => default_places,
+    };
+
+    // Decimal digits of the magnitude, whole part first
+    let mut digits = format!("{}", magnitude >> frac_bits).into_bytes();
+
+    let mut frac = magnitude & mask;
+    for _ in 0..places {
+        frac *= 10;
+        digits.push(b'0' + (frac >> frac_bits) as u8);
+        frac &= mask;
+    }
+
+    // Round half up, carrying as far as it has to go
+    if frac << 1 > mask {
+        let mut carry = true;
 
-        if frac != 0 {
-            frac_str = format!("{:.5}", (frac as f64) / 2f64.powi(PREC))[1..].to_string();
+        for digit in digits.iter_mut().rev() {
+            if *digit == b'9' {
+                *digit = b'0';
+            } else {
+                *digit += 1;
+                carry = false;
+                break;
+            }
         }
 
-        f.write_fmt(format_args!(
-            "{}{}",
-            (self.value.abs() >> PREC) * self.value.signum(),
-            frac_str
-        ))
+        if carry {
+            digits.insert(0, b'1');
+        }
     }
+
+    let whole_len = digits.len() - places;
+    let mut text = String::from_utf8(digits).unwrap();
+    if places > 0 {
+        text.insert(whole_len, '.');
+    }
+
+    f.pad_integral(!negative, "", &text)
 }
 
-impl Default for FixedI32 {
+impl<const FRAC: u32> Default for Fixed<FRAC> {
     fn default() -> Self {
         Self {
             value: i32::default(),
@@ -264,21 +724,21 @@ impl Default for FixedI32 {
     }
 }
 
-impl PartialEq for FixedI32 {
+impl<const FRAC: u32> PartialEq for Fixed<FRAC> {
     fn eq(&self, other: &Self) -> bool {
         self.value == other.value
     }
 }
 
-impl PartialOrd for FixedI32 {
+impl<const FRAC: u32> PartialOrd for Fixed<FRAC> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.value.cmp(&other.value))
     }
 }
 
-impl Eq for FixedI32 {}
+impl<const FRAC: u32> Eq for Fixed<FRAC> {}
 
-impl Ord for FixedI32 {
+impl<const FRAC: u32> Ord for Fixed<FRAC> {
     fn cmp(&self, other: &Self) -> Ordering {
         self.value.cmp(&other.value)
     }
@@ -286,119 +746,164 @@ impl Ord for FixedI32 {
 
 // TODO: Implement these operations all in ARM Assembly.
 
-impl Add for FixedI32 {
+impl<const FRAC: u32> Add for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn add(self, rhs: Self) -> Self::Output {
+        overflow_check!(self.checked_add(rhs), "add");
+
         Self {
             value: self.value + rhs.value,
         }
     }
 }
 
-impl Add<i32> for FixedI32 {
+impl<const FRAC: u32> Add<i32> for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn add(self, rhs: i32) -> Self::Output {
+        overflow_check!(
+            Self::checked_from_int(rhs).and_then(|rhs| self.checked_add(rhs)),
+            "add"
+        );
+
         Self {
-            value: self.value + (rhs << PREC),
+            value: self.value + (rhs << FRAC),
         }
     }
 }
 
-impl AddAssign for FixedI32 {
+impl<const FRAC: u32> AddAssign for Fixed<FRAC> {
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn add_assign(&mut self, rhs: Self) {
+        overflow_check!(self.checked_add(rhs), "add");
+
         self.value += rhs.value;
     }
 }
 
-impl Sub for FixedI32 {
+impl<const FRAC: u32> Sub for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn sub(self, rhs: Self) -> Self::Output {
+        overflow_check!(self.checked_sub(rhs), "sub");
+
         Self {
             value: self.value - rhs.value,
         }
     }
 }
 
-impl Sub<i32> for FixedI32 {
+impl<const FRAC: u32> Sub<i32> for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn sub(self, rhs: i32) -> Self::Output {
+        overflow_check!(
+            Self::checked_from_int(rhs).and_then(|rhs| self.checked_sub(rhs)),
+            "sub"
+        );
+
         Self {
-            value: self.value - (rhs << PREC),
+            value: self.value - (rhs << FRAC),
         }
     }
 }
 
-impl SubAssign for FixedI32 {
+impl<const FRAC: u32> SubAssign for Fixed<FRAC> {
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn sub_assign(&mut self, rhs: Self) {
+        overflow_check!(self.checked_sub(rhs), "sub");
+
         self.value -= rhs.value;
     }
 }
 
-impl Neg for FixedI32 {
+impl<const FRAC: u32> Neg for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn neg(mut self) -> Self::Output {
+        overflow_check!(self.checked_neg(), "neg");
+
         self.value *= -1;
 
         self
     }
 }
 
-impl Mul for FixedI32 {
+impl<const FRAC: u32> Mul for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn mul(self, rhs: Self) -> Self::Output {
+        overflow_check!(self.checked_mul(rhs), "mul");
+
         let res = self.value as i64 * rhs.value as i64;
-        let adj = (res >> PREC) as i32;
+        let adj = (res >> FRAC) as i32;
 
         Self { value: adj }
     }
 }
 
-impl Mul<i32> for FixedI32 {
+impl<const FRAC: u32> Mul<i32> for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn mul(self, rhs: i32) -> Self::Output {
+        overflow_check!(self.checked_mul_int(rhs), "mul");
+
         Self {
             value: self.value * rhs,
         }
     }
 }
 
-impl MulAssign for FixedI32 {
+impl<const FRAC: u32> MulAssign for Fixed<FRAC> {
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn mul_assign(&mut self, rhs: Self) {
+        overflow_check!(self.checked_mul(rhs), "mul");
+
         let res = self.value as i64 * rhs.value as i64;
-        self.value = (res >> PREC) as i32;
+        self.value = (res >> FRAC) as i32;
     }
 }
 
-impl Div for FixedI32 {
+impl<const FRAC: u32> Div for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn div(self, rhs: Self) -> Self::Output {
-        let res = ((self.value as i64) << PREC) / rhs.value as i64;
+        overflow_check!(self.checked_div(rhs), "div");
+
+        let res = ((self.value as i64) << FRAC) / rhs.value as i64;
 
         Self { value: res as i32 }
     }
 }
 
-impl Div<i32> for FixedI32 {
+impl<const FRAC: u32> Div<i32> for Fixed<FRAC> {
     type Output = Self;
 
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn div(self, rhs: i32) -> Self::Output {
+        overflow_check!(self.checked_div_int(rhs), "div");
+
         Self {
             value: self.value / rhs,
         }
     }
 }
 
-impl DivAssign for FixedI32 {
+impl<const FRAC: u32> DivAssign for Fixed<FRAC> {
+    #[cfg_attr(feature = "overflow_check", track_caller)]
     fn div_assign(&mut self, rhs: Self) {
-        let res = ((self.value as i64) << PREC) / rhs.value as i64;
+        overflow_check!(self.checked_div(rhs), "div");
+
+        let res = ((self.value as i64) << FRAC) / rhs.value as i64;
         self.value = res as i32;
     }
 }
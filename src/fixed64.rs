@@ -0,0 +1,159 @@
+use core::{
+    fmt::{self, Display, Formatter},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use crate::fixed::{self, FixedI32};
+
+const PREC: u32 = 32;
+
+/// A Q32.32 fixed point number. A lot slower than `FixedI32` (multiplying and dividing go through
+/// i128), so it only gets used where Q16.16 runs out of bits: squaring distances in the sphere
+/// test, tiny dot products at grazing angles and matrix inverses.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FixedI64 {
+    value: i64,
+}
+
+impl FixedI64 {
+    pub const fn from_bits(value: i64) -> Self {
+        Self { value }
+    }
+
+    pub fn abs(self) -> Self {
+        Self {
+            value: self.value.abs(),
+        }
+    }
+
+    /// Back to Q16.16, rounded to nearest and saturated to its range.
+    pub fn narrow(self) -> FixedI32 {
+        let value = (self.value + (1 << 15)) >> 16;
+        FixedI32::from_bits(value.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+
+    /// Square root, exact to the last bit (rounded down). Negative numbers give 0.
+    pub fn sqrt(self) -> Self {
+        if self.value <= 0 {
+            return Self::default();
+        }
+
+        // sqrt(v / 2^32) * 2^32 = sqrt(v * 2^32), worked out one bit at a time
+        let mut rem = (self.value as u128) << PREC;
+        let mut root = 0u128;
+        let mut bit = 1u128 << 126;
+
+        while bit > rem {
+            bit >>= 2;
+        }
+
+        while bit != 0 {
+            if rem >= root + bit {
+                rem -= root + bit;
+                root = (root >> 1) + bit;
+            } else {
+                root >>= 1;
+            }
+            bit >>= 2;
+        }
+
+        Self { value: root as i64 }
+    }
+}
+
+impl From<FixedI32> for FixedI64 {
+    fn from(value: FixedI32) -> Self {
+        Self {
+            value: (value.to_bits() as i64) << 16,
+        }
+    }
+}
+
+impl From<i32> for FixedI64 {
+    fn from(value: i32) -> Self {
+        Self {
+            value: (value as i64) << PREC,
+        }
+    }
+}
+
+/// Like `FixedI32`'s, but with 9 decimal places by default.
+impl Display for FixedI64 {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        fixed::fmt_fixed(f, self.value < 0, self.value.unsigned_abs(), PREC, 9)
+    }
+}
+
+impl Add for FixedI64 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            value: self.value + rhs.value,
+        }
+    }
+}
+
+impl AddAssign for FixedI64 {
+    fn add_assign(&mut self, rhs: Self) {
+        self.value += rhs.value;
+    }
+}
+
+impl Sub for FixedI64 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            value: self.value - rhs.value,
+        }
+    }
+}
+
+impl SubAssign for FixedI64 {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.value -= rhs.value;
+    }
+}
+
+impl Neg for FixedI64 {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self { value: -self.value }
+    }
+}
+
+impl Mul for FixedI64 {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let res = self.value as i128 * rhs.value as i128;
+
+        Self {
+            value: (res >> PREC) as i64,
+        }
+    }
+}
+
+impl MulAssign for FixedI64 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl Div for FixedI64 {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        let res = ((self.value as i128) << PREC) / rhs.value as i128;
+
+        Self { value: res as i64 }
+    }
+}
+
+impl DivAssign for FixedI64 {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
@@ -0,0 +1,298 @@
+use alloc::{vec, vec::Vec};
+use ndless::{
+    fs::File,
+    io::{self, BufWriter, Read, Write},
+};
+
+use crate::{
+    export::{vec3_channel, write_pfm},
+    filter::Filter,
+    fixed::FixedI32,
+    fxi32,
+    tonemap::ToneMapper,
+    vec3::Vec3FI32,
+};
+
+const FILE_MAGIC: &[u8; 4] = b"NSFB";
+
+/// Turns a display ready color (0 to 1) into RGB555 and RGB888.
+pub fn encode_pixel(color: Vec3FI32) -> (u16, [u8; 3]) {
+    // RGB555
+    let r: i32 = (color.x * 0x1f).into();
+    let g: i32 = (color.y * 0x1f).into();
+    let b: i32 = (color.z * 0x1f).into();
+
+    let r = r.clamp(0, 0x1f) as u16;
+    let g = g.clamp(0, 0x1f) as u16;
+    let b = b.clamp(0, 0x1f) as u16;
+
+    let rgb555 = r << 10 | g << 5 | b;
+
+    // RGB888
+    let r: i32 = (color.x * 0xff).into();
+    let g: i32 = (color.y * 0xff).into();
+    let b: i32 = (color.z * 0xff).into();
+
+    let rgb888 = [
+        r.clamp(0, 0xff) as u8,
+        g.clamp(0, 0xff) as u8,
+        b.clamp(0, 0xff) as u8,
+    ];
+
+    (rgb555, rgb888)
+}
+
+/// Linear HDR color sums for every pixel, weighted by the reconstruction filter and kept in
+/// Q32.32. The tracer still hands back Q16.16 samples, but adding hundreds of bright samples
+/// together would overflow a FixedI32, and dividing each sample by the sample count up front
+/// throws the small ones away.
+///
+/// A framebuffer can also cover just part of a bigger image, starting at (`x`, `y`), which is
+/// how big renders get done in bands that fit in memory.
+pub struct Framebuffer {
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+    sums: Vec<[i64; 3]>,
+    // Q16.16, since every weight is a FixedI32 anyway
+    weights: Vec<i64>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self::new_region(0, 0, width, height)
+    }
+
+    pub fn new_region(x: u16, y: u16, width: u16, height: u16) -> Self {
+        let len = width as usize * height as usize;
+
+        Self {
+            x,
+            y,
+            width,
+            height,
+            sums: vec![[0; 3]; len],
+            weights: vec![0; len],
+        }
+    }
+
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Where the top-left corner of this framebuffer sits in the full image.
+    pub fn origin(&self) -> (u16, u16) {
+        (self.x, self.y)
+    }
+
+    /// Adds all of `other`'s samples that fall inside this framebuffer to it. Since only sums are
+    /// stored, merging the outputs of tile jobs gives the same image as one big render would.
+    pub fn merge(&mut self, other: &Framebuffer) {
+        for i in 0..other.height {
+            for j in 0..other.width {
+                let (x, y) = (other.x + j, other.y + i);
+
+                if x < self.x || y < self.y || x >= self.x + self.width || y >= self.y + self.height
+                {
+                    continue;
+                }
+
+                let src = i as usize * other.width as usize + j as usize;
+                let dst = (y - self.y) as usize * self.width as usize + (x - self.x) as usize;
+
+                for c in 0..3 {
+                    self.sums[dst][c] += other.sums[src][c];
+                }
+                self.weights[dst] += other.weights[src];
+            }
+        }
+    }
+
+    /// Saves the raw sums so that partial renders can be merged later, possibly on a different
+    /// machine. The layout is "NSFB", then x, y, width and height as little endian u16s, then the
+    /// three color sums and the weight of every pixel as little endian i64s.
+    pub fn write_to<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(FILE_MAGIC)?;
+
+        for value in [self.x, self.y, self.width, self.height].iter() {
+            out.write_all(&value.to_le_bytes())?;
+        }
+
+        for (sum, weight) in self.sums.iter().zip(self.weights.iter()) {
+            for value in sum.iter().chain(core::iter::once(weight)) {
+                out.write_all(&value.to_le_bytes())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads back a framebuffer saved with `write_to`.
+    pub fn read_from<R: Read>(input: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        input.read_exact(&mut magic)?;
+
+        if &magic != FILE_MAGIC {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Not an nspray framebuffer",
+            ));
+        }
+
+        let mut header = [0u16; 4];
+        for value in header.iter_mut() {
+            let mut bytes = [0u8; 2];
+            input.read_exact(&mut bytes)?;
+            *value = u16::from_le_bytes(bytes);
+        }
+
+        let mut framebuffer = Self::new_region(header[0], header[1], header[2], header[3]);
+        let mut read_i64 = || -> io::Result<i64> {
+            let mut bytes = [0u8; 8];
+            input.read_exact(&mut bytes)?;
+            Ok(i64::from_le_bytes(bytes))
+        };
+
+        for (sum, weight) in framebuffer
+            .sums
+            .iter_mut()
+            .zip(framebuffer.weights.iter_mut())
+        {
+            for value in sum.iter_mut() {
+                *value = read_i64()?;
+            }
+            *weight = read_i64()?;
+        }
+
+        Ok(framebuffer)
+    }
+
+    /// Moves the framebuffer `rows` rows further down the image, dropping the rows at the top and
+    /// starting empty ones at the bottom.
+    pub fn scroll(&mut self, rows: u16) {
+        let rows = rows.min(self.height) as usize;
+        let shift = rows * self.width as usize;
+
+        self.sums.copy_within(shift.., 0);
+        self.weights.copy_within(shift.., 0);
+
+        let len = self.sums.len();
+        for sum in self.sums[(len - shift)..].iter_mut() {
+            *sum = [0; 3];
+        }
+        for weight in self.weights[(len - shift)..].iter_mut() {
+            *weight = 0;
+        }
+
+        self.y += rows as u16;
+    }
+
+    pub fn add_sample(&mut self, index: usize, color: Vec3FI32, weight: FixedI32) {
+        let sum = &mut self.sums[index];
+        let w = weight.to_bits() as i64;
+
+        // Q16.16 * Q16.16 is exactly Q32.32
+        sum[0] += color.x.to_bits() as i64 * w;
+        sum[1] += color.y.to_bits() as i64 * w;
+        sum[2] += color.z.to_bits() as i64 * w;
+
+        self.weights[index] += w;
+    }
+
+    /// Adds a sample taken at (`x`, `y`), in pixels from the top-left corner of the full image, to
+    /// every pixel of this framebuffer within `filter`'s reach.
+    pub fn splat(&mut self, x: FixedI32, y: FixedI32, color: Vec3FI32, filter: Filter) {
+        let radius = filter.radius();
+
+        // Pixel centers are at +0.5
+        let x = x - fxi32!(0.5) - self.x as i32;
+        let y = y - fxi32!(0.5) - self.y as i32;
+
+        let first_col: i32 = (x - radius).into();
+        let last_col: i32 = (x + radius).into();
+        let first_row: i32 = (y - radius).into();
+        let last_row: i32 = (y + radius).into();
+
+        for row in first_row.max(0)..=last_row.min(self.height as i32 - 1) {
+            for col in first_col.max(0)..=last_col.min(self.width as i32 - 1) {
+                let weight = filter.weight(x - col, y - row);
+
+                if weight != fxi32!(0) {
+                    let index = row as usize * self.width as usize + col as usize;
+                    self.add_sample(index, color, weight);
+                }
+            }
+        }
+    }
+
+    /// The weighted average of all samples that landed on the pixel at `index`, saturated to the
+    /// FixedI32 range.
+    pub fn pixel(&self, index: usize) -> Vec3FI32 {
+        let weight = self.weights[index];
+
+        // Mitchell's negative lobes can cancel everything out in theory
+        if weight <= 0 {
+            return Vec3FI32::default();
+        }
+
+        let resolve = |sum: i64| {
+            // Q32.32 / Q16.16 = Q16.16, rounded to nearest
+            let mean = (sum + sum.signum() * weight / 2) / weight;
+            FixedI32::from_bits(mean.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+        };
+
+        let sum = self.sums[index];
+        Vec3FI32::new(resolve(sum[0]), resolve(sum[1]), resolve(sum[2]))
+    }
+
+    /// Tone maps the pixel at `index`, returning it as RGB555 and RGB888.
+    pub fn resolve_pixel(&self, index: usize, tone_mapper: &ToneMapper) -> (u16, [u8; 3]) {
+        encode_pixel(tone_mapper.map(self.pixel(index)))
+    }
+
+    /// Tone maps row `row` (of this framebuffer) into the RGB555 screen buffer and the RGB888
+    /// buffer that gets dithered. Both buffers have the same size as the framebuffer.
+    pub fn resolve_row(
+        &self,
+        row: u16,
+        tone_mapper: &ToneMapper,
+        screen_buff: &mut [u16],
+        rgb_buff: &mut [u8],
+    ) {
+        let start = row as usize * self.width as usize;
+
+        for index in start..(start + self.width as usize) {
+            let (rgb555, rgb888) = self.resolve_pixel(index, tone_mapper);
+
+            screen_buff[index] = rgb555;
+            rgb_buff[index * 3..index * 3 + 3].copy_from_slice(&rgb888);
+        }
+    }
+
+    /// Tone maps the whole image, see `resolve_row`.
+    pub fn resolve(&self, tone_mapper: &ToneMapper, screen_buff: &mut [u16], rgb_buff: &mut [u8]) {
+        for row in 0..self.height {
+            self.resolve_row(row, tone_mapper, screen_buff, rgb_buff);
+        }
+    }
+
+    /// Writes the linear, un-tone-mapped image as a PFM so it can be graded on a PC.
+    pub fn export_pfm(&self, path: &str) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        write_pfm(
+            &mut file,
+            self.width as usize,
+            self.height as usize,
+            3,
+            |i, c| vec3_channel(self.pixel(i), c),
+        )?;
+
+        file.flush()
+    }
+}
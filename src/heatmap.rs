@@ -0,0 +1,55 @@
+use crate::{
+    fixed::FixedI32,
+    framebuffer::{encode_pixel, Framebuffer},
+    fxi32,
+    vec3::Vec3FI32,
+};
+
+/// Dark blue for the cheapest pixels, through green and yellow to red for the most expensive ones.
+const STOPS: [[FixedI32; 3]; 5] = [
+    [fxi32!(0), fxi32!(0), fxi32!(0.25)],
+    [fxi32!(0), fxi32!(0.25), fxi32!(1)],
+    [fxi32!(0), fxi32!(1), fxi32!(0.25)],
+    [fxi32!(1), fxi32!(1), fxi32!(0)],
+    [fxi32!(1), fxi32!(0), fxi32!(0)],
+];
+
+/// The false color for `t`, which goes from 0 (cheap) to 1 (expensive).
+pub fn false_color(t: FixedI32) -> Vec3FI32 {
+    let t = t.max(fxi32!(0)).min(fxi32!(1)) * (STOPS.len() as i32 - 1);
+    let segment: i32 = t.into();
+    let segment = (segment as usize).min(STOPS.len() - 2);
+    let f = t - segment as i32;
+
+    let (a, b) = (STOPS[segment], STOPS[segment + 1]);
+    let channel = |c: usize| a[c] + f * (b[c] - a[c]);
+
+    Vec3FI32::new(channel(0), channel(1), channel(2))
+}
+
+/// Resolves a framebuffer that was rendered in one of the cost modes (see `RenderMode`) into
+/// false colors, scaled so that the most expensive pixel comes out red. Returns that pixel's cost
+/// per sample, to know what the colors mean.
+pub fn resolve(
+    framebuffer: &Framebuffer,
+    screen_buff: &mut [u16],
+    rgb_buff: &mut [u8],
+) -> FixedI32 {
+    let len = framebuffer.width() as usize * framebuffer.height() as usize;
+
+    // The cost went into every channel, any of them will do
+    let max = (0..len)
+        .map(|i| framebuffer.pixel(i).x)
+        .max()
+        .unwrap_or_default()
+        .max(fxi32!(1));
+
+    for i in 0..len {
+        let (rgb555, rgb888) = encode_pixel(false_color(framebuffer.pixel(i).x / max));
+
+        screen_buff[i] = rgb555;
+        rgb_buff[i * 3..i * 3 + 3].copy_from_slice(&rgb888);
+    }
+
+    max
+}
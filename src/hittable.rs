@@ -1,50 +1,61 @@
-use alloc::{boxed::Box, format, rc::Rc, vec::Vec};
+use alloc::{boxed::Box, format, vec::Vec};
 
 use crate::{
     dprintln,
     fixed::FixedI32,
     fxi32,
     material::Material,
-    matrix::{Matrix3x3, Matrix3x3FI32},
+    matrix::Matrix3x3,
     ray::Ray,
-    vec3::Vec3FI32,
+    scalar::Scalar,
+    stats::{Primitive, RenderStats},
+    sync::{Shared, ThreadSafe},
+    vec3::Vec3,
 };
+#[cfg(feature = "fixed64")]
+use crate::{fixed64::FixedI64, vec3::Vec3FI32};
 
 #[derive(Default, Clone)]
-pub struct HitRecord {
-    pub point: Vec3FI32,
-    pub mapped_point: Option<Vec3FI32>,
-    pub normal: Vec3FI32,
-    pub t: FixedI32,
+pub struct HitRecord<T: Scalar = FixedI32> {
+    pub point: Vec3<T>,
+    pub mapped_point: Option<Vec3<T>>,
+    pub normal: Vec3<T>,
+    pub t: T,
     pub front: bool,
-    pub attenuation: Vec3FI32,
-    pub material: Option<Rc<dyn Material>>,
+    pub attenuation: Vec3<T>,
+    pub material: Option<Shared<dyn Material<T>>>,
+    pub object_id: u16,
 }
 
-impl HitRecord {
-    pub fn set_face_normal(&mut self, ray: &Ray, outward_normal: Vec3FI32) {
-        self.front = ray.dir().dot(outward_normal) < fxi32!(0);
+impl<T: Scalar> HitRecord<T> {
+    pub fn set_face_normal(&mut self, ray: &Ray<T>, outward_normal: Vec3<T>) {
+        self.front = ray.dir().dot(outward_normal) < T::ZERO;
         self.normal = if self.front {
             outward_normal
         } else {
-            outward_normal * fxi32!(-1)
+            outward_normal * -T::ONE
         };
     }
 }
 
-pub trait Hittable {
-    fn hit(&self, ray: &Ray, record: &mut HitRecord, t_min: FixedI32, t_max: FixedI32) -> bool;
+pub trait Hittable<T: Scalar = FixedI32>: ThreadSafe {
+    fn hit(&self, ray: &Ray<T>, record: &mut HitRecord<T>, t_min: T, t_max: T) -> bool;
+
+    /// What intersection tests against this get counted as in the render stats.
+    fn primitive(&self) -> Primitive {
+        Primitive::Other
+    }
 }
 
 #[derive(Default)]
-pub struct Sphere {
-    center: Vec3FI32,
-    radius: FixedI32,
-    material: Option<Rc<dyn Material>>,
+pub struct Sphere<T: Scalar = FixedI32> {
+    center: Vec3<T>,
+    radius: T,
+    material: Option<Shared<dyn Material<T>>>,
 }
 
-impl Sphere {
-    pub fn new(center: Vec3FI32, radius: FixedI32, material: Option<Rc<dyn Material>>) -> Self {
+impl<T: Scalar> Sphere<T> {
+    pub fn new(center: Vec3<T>, radius: T, material: Option<Shared<dyn Material<T>>>) -> Self {
         Self {
             center,
             radius,
@@ -53,28 +64,21 @@ impl Sphere {
     }
 }
 
-impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, record: &mut HitRecord, t_min: FixedI32, t_max: FixedI32) -> bool {
-        let oc = ray.origin() - self.center;
-
-        let a = ray.dir().mag_squared();
-        let half_b = oc.dot(ray.dir());
-        let c = oc.mag_squared() - self.radius * self.radius;
-
-        // lol
-        #[allow(clippy::suspicious_operation_groupings)]
-        let discriminant = half_b * half_b - a * c;
-
-        if discriminant < fxi32!(0) {
-            return false;
-        }
+impl<T: Scalar> Hittable<T> for Sphere<T> {
+    fn primitive(&self) -> Primitive {
+        Primitive::Sphere
+    }
 
-        let sqrtd = discriminant.sqrt();
+    fn hit(&self, ray: &Ray<T>, record: &mut HitRecord<T>, t_min: T, t_max: T) -> bool {
+        let (near, far) = match T::sphere_roots(self.center, self.radius, ray) {
+            Some(roots) => roots,
+            None => return false,
+        };
 
-        let mut root = (-half_b - sqrtd) / a;
+        let mut root = near;
 
         if root < t_min || root > t_max {
-            root = (-half_b + sqrtd) / a;
+            root = far;
             if root < t_min || root > t_max {
                 return false;
             }
@@ -93,29 +97,25 @@ impl Hittable for Sphere {
     }
 }
 
-pub struct Plane {
-    center: Vec3FI32,
-    normal: Vec3FI32,
-    inverse: Matrix3x3FI32,
-    material: Option<Rc<dyn Material>>,
+pub struct Plane<T: Scalar = FixedI32> {
+    center: Vec3<T>,
+    normal: Vec3<T>,
+    inverse: Matrix3x3<T>,
+    material: Option<Shared<dyn Material<T>>>,
 }
 
-impl Plane {
+impl<T: Scalar> Plane<T> {
     pub fn new(
-        center: Vec3FI32,
-        v: Vec3FI32,
-        u: Vec3FI32,
-        material: Option<Rc<dyn Material>>,
+        center: Vec3<T>,
+        v: Vec3<T>,
+        u: Vec3<T>,
+        material: Option<Shared<dyn Material<T>>>,
     ) -> Self {
         let normal = v.cross(u);
-        let inverse =
-            Matrix3x3FI32::new([v.x, u.x, normal.x, v.y, u.y, normal.y, v.z, u.z, normal.z]);
+        let matrix = Matrix3x3::new([v.x, u.x, normal.x, v.y, u.y, normal.y, v.z, u.z, normal.z]);
+        let inverse = T::invert_matrix(matrix);
 
-        dprintln!(
-            "v: {v}, u: {u}, n: {normal}, mat: {inverse} inv: {}",
-            inverse.invert()
-        );
-        let inverse = inverse.invert();
+        dprintln!("v: {v}, u: {u}, n: {normal}, mat: {matrix} inv: {inverse}");
 
         Self {
             center,
@@ -126,15 +126,16 @@ impl Plane {
     }
 }
 
-impl Hittable for Plane {
-    fn hit(&self, ray: &Ray, record: &mut HitRecord, t_min: FixedI32, t_max: FixedI32) -> bool {
-        let denom = ray.dir().dot(self.normal);
-
-        if denom.abs() < fxi32!(0.01) {
-            return false;
-        }
+impl<T: Scalar> Hittable<T> for Plane<T> {
+    fn primitive(&self) -> Primitive {
+        Primitive::Plane
+    }
 
-        let t = (self.center - ray.origin()).dot(self.normal) / denom;
+    fn hit(&self, ray: &Ray<T>, record: &mut HitRecord<T>, t_min: T, t_max: T) -> bool {
+        let t = match T::plane_distance(self.center, self.normal, ray) {
+            Some(t) => t,
+            None => return false,
+        };
 
         if t < t_min || t > t_max {
             return false;
@@ -152,26 +153,37 @@ impl Hittable for Plane {
     }
 }
 
-pub struct HittableList {
-    objects: Vec<Box<dyn Hittable>>,
+pub struct HittableList<T: Scalar = FixedI32> {
+    objects: Vec<Box<dyn Hittable<T>>>,
 }
 
-impl HittableList {
-    pub fn new(objects: Vec<Box<dyn Hittable>>) -> Self {
+impl<T: Scalar> HittableList<T> {
+    pub fn new(objects: Vec<Box<dyn Hittable<T>>>) -> Self {
         HittableList { objects }
     }
-}
 
-impl Hittable for HittableList {
-    fn hit(&self, ray: &Ray, record: &mut HitRecord, t_min: FixedI32, t_max: FixedI32) -> bool {
+    /// `hit`, but counting every intersection test in `stats`.
+    pub fn hit_with_stats(
+        &self,
+        ray: &Ray<T>,
+        record: &mut HitRecord<T>,
+        t_min: T,
+        t_max: T,
+        mut stats: Option<&mut RenderStats>,
+    ) -> bool {
         let mut min_dist = t_max;
         let mut has_hit = false;
         let mut tmp_rec = HitRecord::default();
 
-        for object in self.objects.iter() {
+        for (id, object) in self.objects.iter().enumerate() {
+            if let Some(stats) = stats.as_deref_mut() {
+                stats.count_test(object.primitive());
+            }
+
             if object.hit(ray, &mut tmp_rec, t_min, t_max) {
                 has_hit = true;
                 if tmp_rec.t < min_dist {
+                    tmp_rec.object_id = id as u16;
                     *record = tmp_rec.clone();
                     min_dist = record.t;
                 }
@@ -181,3 +193,107 @@ impl Hittable for HittableList {
         has_hit
     }
 }
+
+impl<T: Scalar> Hittable<T> for HittableList<T> {
+    fn hit(&self, ray: &Ray<T>, record: &mut HitRecord<T>, t_min: T, t_max: T) -> bool {
+        self.hit_with_stats(ray, record, t_min, t_max, None)
+    }
+}
+
+/// See `Scalar::sphere_roots`.
+pub fn sphere_roots<T: Scalar>(center: Vec3<T>, radius: T, ray: &Ray<T>) -> Option<(T, T)> {
+    let oc = ray.origin() - center;
+
+    let a = ray.dir().mag_squared();
+    let half_b = oc.dot(ray.dir());
+    let c = oc.mag_squared() - radius * radius;
+
+    // lol
+    #[allow(clippy::suspicious_operation_groupings)]
+    let discriminant = half_b * half_b - a * c;
+
+    if discriminant < T::ZERO {
+        return None;
+    }
+
+    let sqrtd = discriminant.sqrt();
+
+    Some(((-half_b - sqrtd) / a, (-half_b + sqrtd) / a))
+}
+
+/// Same as above, but in Q32.32 so big or far away spheres don't overflow the discriminant.
+#[cfg(feature = "fixed64")]
+pub fn sphere_roots_wide(
+    center: Vec3FI32,
+    radius: FixedI32,
+    ray: &Ray,
+) -> Option<(FixedI32, FixedI32)> {
+    let oc = wide(ray.origin()) - wide(center);
+    let dir = wide(ray.dir());
+    let radius = FixedI64::from(radius);
+
+    let a = dir.mag_squared();
+    let half_b = oc.dot(dir);
+    let c = oc.mag_squared() - radius * radius;
+
+    #[allow(clippy::suspicious_operation_groupings)]
+    let discriminant = half_b * half_b - a * c;
+
+    if discriminant < FixedI64::default() {
+        return None;
+    }
+
+    let sqrtd = discriminant.sqrt();
+
+    Some((
+        ((-half_b - sqrtd) / a).narrow(),
+        ((-half_b + sqrtd) / a).narrow(),
+    ))
+}
+
+/// See `Scalar::plane_distance`.
+pub fn plane_distance<T: Scalar>(center: Vec3<T>, normal: Vec3<T>, ray: &Ray<T>) -> Option<T> {
+    let denom = ray.dir().dot(normal);
+
+    if denom.abs() < T::from_fixed(fxi32!(0.01)) {
+        return None;
+    }
+
+    Some((center - ray.origin()).dot(normal) / denom)
+}
+
+/// Same as above, but in Q32.32, which only has to give up on rays that are parallel to the
+/// plane to a few millionths instead of a hundredth.
+#[cfg(feature = "fixed64")]
+pub fn plane_distance_wide(center: Vec3FI32, normal: Vec3FI32, ray: &Ray) -> Option<FixedI32> {
+    let normal = wide(normal);
+    let denom = wide(ray.dir()).dot(normal);
+
+    if denom.abs() < FixedI64::from_bits(1 << 16) {
+        return None;
+    }
+
+    Some(((wide(center) - wide(ray.origin())).dot(normal) / denom).narrow())
+}
+
+/// Inverts in Q32.32, since the determinant of a plane's basis is a product of three Q16.16 numbers
+/// and loses most of its bits for small or skewed planes.
+#[cfg(feature = "fixed64")]
+pub fn invert_wide(matrix: Matrix3x3<FixedI32>) -> Matrix3x3<FixedI32> {
+    let mut wide = Matrix3x3::<FixedI64>::default();
+    for (to, from) in wide.state.iter_mut().zip(matrix.state.iter()) {
+        *to = (*from).into();
+    }
+
+    let mut inverse = Matrix3x3::<FixedI32>::default();
+    for (to, from) in inverse.state.iter_mut().zip(wide.invert().state.iter()) {
+        *to = from.narrow();
+    }
+
+    inverse
+}
+
+#[cfg(feature = "fixed64")]
+fn wide(v: Vec3FI32) -> Vec3<FixedI64> {
+    Vec3::new(v.x.into(), v.y.into(), v.z.into())
+}
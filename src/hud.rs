@@ -0,0 +1,175 @@
+use alloc::{format, string::String};
+use core::time::Duration;
+
+use crate::{
+    clock::Stopwatch,
+    screen::{LCD_HEIGHT, LCD_WIDTH},
+};
+
+/// Size of a font pixel on the screen.
+const SCALE: usize = 2;
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+const MARGIN: usize = 2;
+
+const TEXT_COLOR: u16 = 0x7fff;
+const BACKGROUND_COLOR: u16 = 0;
+
+/// A 3x5 font with just the characters the HUD needs, one row per byte with the leftmost pixel
+/// in bit 2. Anything else comes out blank.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        ':' => [0b000, 0b010, 0b000, 0b010, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        _ => [0; GLYPH_HEIGHT],
+    }
+}
+
+/// Draws `text` on a black box with its top-left corner at (`x`, `y`) of the LCD sized `buffer`.
+pub fn draw_text(buffer: &mut [u16], x: usize, y: usize, text: &str) {
+    let advance = (GLYPH_WIDTH + 1) * SCALE;
+    let box_width = (text.chars().count() * advance + SCALE).min(LCD_WIDTH.saturating_sub(x));
+    let box_height = ((GLYPH_HEIGHT + 2) * SCALE).min(LCD_HEIGHT.saturating_sub(y));
+
+    for i in y..(y + box_height) {
+        for pixel in buffer[(i * LCD_WIDTH + x)..(i * LCD_WIDTH + x + box_width)].iter_mut() {
+            *pixel = BACKGROUND_COLOR;
+        }
+    }
+
+    for (n, c) in text.chars().enumerate() {
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+
+                let px = x + SCALE + n * advance + col * SCALE;
+                let py = y + SCALE + row * SCALE;
+
+                for i in py..(py + SCALE) {
+                    for j in px..(px + SCALE) {
+                        if i < LCD_HEIGHT && j < LCD_WIDTH {
+                            buffer[i * LCD_WIDTH + j] = TEXT_COLOR;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Draws a small crosshair centered on (`x`, `y`) of the LCD sized `buffer`, inverting the pixels
+/// under it so that it shows up on any background.
+pub fn draw_cursor(buffer: &mut [u16], x: usize, y: usize) {
+    for offset in 2..6 {
+        let points = [
+            (x.wrapping_sub(offset), y),
+            (x + offset, y),
+            (x, y.wrapping_sub(offset)),
+            (x, y + offset),
+        ];
+
+        for &(px, py) in points.iter() {
+            if px < LCD_WIDTH && py < LCD_HEIGHT {
+                buffer[py * LCD_WIDTH + px] ^= 0x7fff;
+            }
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// The progress overlay that gets drawn over the preview while rendering. It only ever goes on
+/// the LCD buffer, never into the image itself.
+pub struct Hud {
+    stopwatch: Stopwatch,
+    visible: bool,
+}
+
+impl Hud {
+    /// Starts the clock for the elapsed time and ETA.
+    pub fn new() -> Self {
+        Self {
+            stopwatch: Stopwatch::start(),
+            visible: true,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.stopwatch.elapsed()
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Draws the HUD into `lcd_buff` for a render that's `done` out of `total` (in any unit) of
+    /// the way there, having traced `rays` camera rays so far. A `rays` of 0 leaves out the speed.
+    pub fn draw(&self, lcd_buff: &mut [u16], done: u64, total: u64, rays: u64) {
+        if !self.visible {
+            return;
+        }
+
+        let elapsed = self.elapsed();
+        let line = (GLYPH_HEIGHT + 2) * SCALE;
+
+        let percent = if total == 0 { 100 } else { done * 100 / total };
+        draw_text(
+            lcd_buff,
+            MARGIN,
+            MARGIN,
+            &format!("{}% {}", percent, format_duration(elapsed)),
+        );
+
+        if done > 0 && done < total {
+            let eta = elapsed.as_secs() * (total - done) / done;
+            draw_text(
+                lcd_buff,
+                MARGIN,
+                MARGIN + line,
+                &format!("ETA {}", format_duration(Duration::from_secs(eta))),
+            );
+        }
+
+        if rays > 0 {
+            let millis = elapsed.as_millis().max(1) as u64;
+            draw_text(
+                lcd_buff,
+                MARGIN,
+                MARGIN + 2 * line,
+                &format!("{} RAYS/S", rays * 1000 / millis),
+            );
+        }
+    }
+}
+
+impl Default for Hud {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -11,18 +11,36 @@
  * wish you luck.
  */
 
-use alloc::{boxed::Box, rc::Rc, vec, vec::Vec};
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+use aov::{Aov, AovBuffers};
 use camera::Camera;
-use caster::Renderer;
+use caster::{RenderControl, RenderMode, Renderer};
+use core::ops::Range;
+use filter::Filter;
 use fixed::FixedI32;
+use framebuffer::Framebuffer;
 use hittable::{Hittable, HittableList, Sphere};
+use hud::Hud;
 use material::{Lambertian, Metal};
 use ndless::{
-    fs::File, input::wait_key_pressed, io::BufWriter, io::Write, msg::Button, time::SystemTime,
+    fs::File,
+    input::{is_key_pressed, wait_key_pressed, wait_no_key_pressed, Key},
+    io::BufWriter,
+    io::Write,
+    msg::Button,
+    time::{Duration, SystemTime},
 };
 use oorandom::Rand32;
-use screen::{blit_buffer, deinit_screen, init_screen};
-use vec3::Vec3FI32;
+use scalar::Scalar;
+use screen::{
+    blit_buffer, blit_image, deinit_screen, init_screen, stretch_image, Scaling, LCD_HEIGHT,
+    LCD_WIDTH,
+};
+use stats::RenderStats;
+use sync::Shared;
+use tile::TileJob;
+use tonemap::{ToneMapper, ToneOperator};
+use vec3::{Vec3, Vec3FI32};
 
 use crate::{hittable::Plane, material::CheckeredLambertian};
 
@@ -34,98 +52,207 @@ extern crate ndless_handler;
 
 extern crate alloc;
 extern crate oorandom;
+#[cfg(feature = "std")]
+extern crate std;
 
+mod aov;
 mod camera;
 mod caster;
+mod clock;
+#[cfg(feature = "std")]
+mod compare;
 mod debug;
+mod denoise;
 mod dither;
+mod exp;
+mod export;
+mod filter;
 mod fixed;
+#[cfg(feature = "fixed64")]
+mod fixed64;
+mod framebuffer;
+mod heatmap;
 mod hittable;
+mod hud;
 mod material;
 mod matrix;
+mod overflow;
 mod ray;
+mod scalar;
 mod screen;
+mod stats;
+mod sync;
 mod tests;
+#[cfg(feature = "std")]
+mod threaded;
+mod tile;
+mod tonemap;
+mod trig;
 mod vec3;
 
-const IMG_WIDTH: usize = 320;
-const IMG_HEIGHT: usize = 240;
+/// Rows rendered at a time when writing big images straight to a file.
+const FILE_BAND_ROWS: u16 = 32;
+
+/// Every calculator working on the same render has to use the same seed for the tiles to fit.
+const RENDER_SEED: u64 = 2;
+
+/// What this run should do with the render.
+enum Job {
+    Whole,
+    /// Render tile `.0` (starting at 1) out of `.1` and save it.
+    Tile(u16, u16),
+    /// Put the `.0` saved tiles back together.
+    Merge(u16),
+}
+
+fn tile_path(tile: u16) -> String {
+    format!("nspray_tile_{tile}.fb.tns")
+}
+
+/// Called while rendering: ESC stops the render, enter pauses it until enter gets pressed again
+/// and tab shows or hides the HUD.
+fn poll_keys(paused: &mut bool, hud: &mut Hud) -> RenderControl {
+    if is_key_pressed(Key::Esc) {
+        wait_no_key_pressed();
+        return RenderControl::Abort;
+    }
+
+    if is_key_pressed(Key::Enter) {
+        wait_no_key_pressed();
+        *paused = !*paused;
+    }
+
+    if is_key_pressed(Key::Tab) {
+        wait_no_key_pressed();
+        hud.toggle();
+    }
+
+    if *paused {
+        RenderControl::Pause
+    } else {
+        RenderControl::Continue
+    }
+}
+
+/// Lets the user move a cursor over the finished image with the arrow keys and trace the pixel
+/// under it into the log with enter, until ESC gets pressed.
+fn pixel_debugger(
+    renderer: &Renderer,
+    passes: &[Range<u16>],
+    image: &[u16],
+    width: usize,
+    height: usize,
+    lcd_buff: &mut [u16],
+    scaling: Scaling,
+) {
+    let (mut x, mut y) = (width / 2, height / 2);
+
+    loop {
+        stretch_image(image, width, height, lcd_buff, scaling);
+        hud::draw_cursor(
+            lcd_buff,
+            (2 * x + 1) * LCD_WIDTH / (2 * width),
+            (2 * y + 1) * LCD_HEIGHT / (2 * height),
+        );
+        hud::draw_text(lcd_buff, 2, 2, &format!("{x} {y}"));
+        blit_buffer(lcd_buff);
+
+        wait_key_pressed();
+
+        if is_key_pressed(Key::Esc) {
+            wait_no_key_pressed();
+            return;
+        }
+
+        if is_key_pressed(Key::Enter) {
+            renderer.debug_pixel(x as u16, y as u16, passes);
+            wait_no_key_pressed();
+        }
+
+        // Holding an arrow keeps the cursor going
+        if is_key_pressed(Key::Left) {
+            x = x.saturating_sub(1);
+        }
+        if is_key_pressed(Key::Right) {
+            x = (x + 1).min(width - 1);
+        }
+        if is_key_pressed(Key::Up) {
+            y = y.saturating_sub(1);
+        }
+        if is_key_pressed(Key::Down) {
+            y = (y + 1).min(height - 1);
+        }
+    }
+}
 
 // This is a really bad idea
 static mut LOG_FILE: Option<BufWriter<File>> = None;
 static mut START_TIME: Option<SystemTime> = None;
 
-fn gen_scene(rand: &mut Rand32) -> HittableList {
-    let ground_material = Rc::new(CheckeredLambertian::new(
-        Vec3FI32::new(fxi32!(0.95), fxi32!(0.95), fxi32!(0.2)),
-        Vec3FI32::new(fxi32!(0.2), fxi32!(0.2), fxi32!(0.95)),
+/// The scene that gets rendered, in any `Scalar` so that the host can render a float reference of
+/// it. Everything starts out as fixed point numbers, so the scene is the same either way.
+fn gen_scene<T: Scalar>(rand: &mut Rand32) -> HittableList<T> {
+    let v = |x, y, z| Vec3::from_fixed(Vec3FI32::new(x, y, z));
+    let s = T::from_fixed;
+
+    let ground_material = Shared::new(CheckeredLambertian::new(
+        v(fxi32!(0.95), fxi32!(0.95), fxi32!(0.2)),
+        v(fxi32!(0.2), fxi32!(0.2), fxi32!(0.95)),
     ));
 
-    let red_ball_mat = Rc::new(Lambertian::new(Vec3FI32::new(
-        fxi32!(0.95),
-        fxi32!(0.2),
-        fxi32!(0.2),
-    )));
-
-    let green_ball_mat = Rc::new(Lambertian::new(Vec3FI32::new(
-        fxi32!(0.2),
-        fxi32!(0.95),
-        fxi32!(0.2),
-    )));
-
-    let blue_ball_mat = Rc::new(Lambertian::new(Vec3FI32::new(
-        fxi32!(0.2),
-        fxi32!(0.2),
-        fxi32!(0.95),
-    )));
-
-    let metal_ball_mat = Rc::new(Metal::new(
-        fxi32!(0.5),
-        Vec3FI32::new(fxi32!(0.7), fxi32!(0.7), fxi32!(0.7)),
+    let red_ball_mat = Shared::new(Lambertian::new(v(fxi32!(0.95), fxi32!(0.2), fxi32!(0.2))));
+
+    let green_ball_mat = Shared::new(Lambertian::new(v(fxi32!(0.2), fxi32!(0.95), fxi32!(0.2))));
+
+    let blue_ball_mat = Shared::new(Lambertian::new(v(fxi32!(0.2), fxi32!(0.2), fxi32!(0.95))));
+
+    let metal_ball_mat = Shared::new(Metal::new(
+        s(fxi32!(0.5)),
+        v(fxi32!(0.7), fxi32!(0.7), fxi32!(0.7)),
     ));
 
-    let mirror_ball_mat = Rc::new(Metal::new(
-        fxi32!(0.1),
-        Vec3FI32::new(fxi32!(0.7), fxi32!(0.7), fxi32!(0.7)),
+    let mirror_ball_mat = Shared::new(Metal::new(
+        s(fxi32!(0.1)),
+        v(fxi32!(0.7), fxi32!(0.7), fxi32!(0.7)),
     ));
 
-    let mut world_vec: Vec<Box<dyn Hittable>> = vec![
+    let mut world_vec: Vec<Box<dyn Hittable<T>>> = vec![
         Box::new(Plane::new(
-            Vec3FI32::new(fxi32!(0.0), fxi32!(-1.0), fxi32!(0.0)),
-            Vec3FI32::new(fxi32!(0.0), fxi32!(0.0), fxi32!(1.0)),
-            Vec3FI32::new(fxi32!(1.0), fxi32!(0.0), fxi32!(0.0)),
+            v(fxi32!(0.0), fxi32!(-1.0), fxi32!(0.0)),
+            v(fxi32!(0.0), fxi32!(0.0), fxi32!(1.0)),
+            v(fxi32!(1.0), fxi32!(0.0), fxi32!(0.0)),
             Some(ground_material),
         )),
         Box::new(Sphere::new(
-            Vec3FI32::new(fxi32!(2.0), fxi32!(2), fxi32!(-2)),
-            fxi32!(2),
+            v(fxi32!(2.0), fxi32!(2), fxi32!(-2)),
+            s(fxi32!(2)),
             Some(red_ball_mat.clone()),
         )),
         Box::new(Sphere::new(
-            Vec3FI32::new(fxi32!(4.0), fxi32!(2), fxi32!(2)),
-            fxi32!(2),
+            v(fxi32!(4.0), fxi32!(2), fxi32!(2)),
+            s(fxi32!(2)),
             Some(green_ball_mat.clone()),
         )),
         Box::new(Sphere::new(
-            Vec3FI32::new(fxi32!(6.0), fxi32!(2), fxi32!(-2)),
-            fxi32!(2),
+            v(fxi32!(6.0), fxi32!(2), fxi32!(-2)),
+            s(fxi32!(2)),
             Some(blue_ball_mat.clone()),
         )),
         Box::new(Sphere::new(
-            Vec3FI32::new(fxi32!(-2.5), fxi32!(2), fxi32!(-2)),
-            fxi32!(2),
+            v(fxi32!(-2.5), fxi32!(2), fxi32!(-2)),
+            s(fxi32!(2)),
             Some(mirror_ball_mat.clone()),
         )),
     ];
 
     for _ in 0..30 {
         world_vec.push(Box::new(Sphere::new(
-            Vec3FI32::new(
+            v(
                 FixedI32::rand(rand) * 30 - fxi32!(15),
                 fxi32!(0),
                 FixedI32::rand(rand) * 30 - fxi32!(15),
             ),
-            fxi32!(1.0),
+            s(fxi32!(1.0)),
             Some(metal_ball_mat.clone()),
         )));
     }
@@ -133,15 +260,126 @@ fn gen_scene(rand: &mut Rand32) -> HittableList {
     HittableList::new(world_vec)
 }
 
+/// The camera looking at `gen_scene`, see there for why it's generic.
+fn gen_camera<T: Scalar>(width: u16, height: u16, lens_blur: bool) -> Camera<T> {
+    let v = |x, y, z| Vec3::from_fixed(Vec3FI32::new(x, y, z));
+
+    let apeture = if lens_blur { fxi32!(0.1) } else { fxi32!(0) };
+    let focus_dist = if lens_blur { fxi32!(10) } else { fxi32!(1) };
+
+    Camera::new(
+        v(fxi32!(2), fxi32!(6), fxi32!(6)),
+        v(fxi32!(0), fxi32!(0), fxi32!(0)),
+        v(fxi32!(0), fxi32!(1), fxi32!(0)),
+        T::from_fixed(fxi32!(45)),
+        T::from_fixed(fxi32!(width as i32) / fxi32!(height as i32)),
+        T::from_fixed(apeture),
+        T::from_fixed(focus_dist),
+    )
+}
+
 fn main() {
-    let mut screen_buff = vec![0u16; IMG_WIDTH * IMG_HEIGHT];
-    let mut rgb_buff = vec![0u8; IMG_WIDTH * IMG_HEIGHT * 3];
+    #[cfg(feature = "std")]
+    {
+        if threaded::run_from_args() || compare::run_from_args() {
+            return;
+        }
+    }
 
-    let sample_count = ndless::msg::msg_numeric("Sample Input", "", "How many samples?", (1, 100));
-    if sample_count.is_none() {
+    let timed = ndless::msg::msg_2b(
+        "Render Length",
+        "Do you want to render a set number of samples or for a set time",
+        "Samples",
+        "Time",
+    ) == Button::Two;
+
+    let (sample_count, budget) = if timed {
+        let minutes = ndless::msg::msg_numeric("Time Input", "", "How many minutes?", (1, 600));
+        if minutes.is_none() {
+            return;
+        }
+
+        (1, Some(Duration::from_secs(minutes.unwrap() as u64 * 60)))
+    } else {
+        let sample_count =
+            ndless::msg::msg_numeric("Sample Input", "", "How many samples?", (1, 100));
+        if sample_count.is_none() {
+            return;
+        }
+
+        (sample_count.unwrap(), None)
+    };
+
+    let size = ndless::msg::msg_numeric(
+        "Resolution",
+        "",
+        "Size (1 160x120 preview, 2 320x240, 3 1280x960 to a file)",
+        (1, 3),
+    );
+    if size.is_none() {
         return;
     }
-    let sample_count = sample_count.unwrap();
+    let size = size.unwrap();
+
+    let (width, height) = match size {
+        1 => (160, 120),
+        3 => (1280, 960),
+        _ => (LCD_WIDTH, LCD_HEIGHT),
+    };
+    // Too big to keep in memory, so it gets streamed to a file as it renders
+    let to_file = size == 3;
+
+    let scaling = if width < LCD_WIDTH
+        && ndless::msg::msg_2b(
+            "Upscaling",
+            "How should the preview be stretched to the screen",
+            "Nearest",
+            "Bilinear",
+        ) == Button::Two
+    {
+        Scaling::Bilinear
+    } else {
+        Scaling::Nearest
+    };
+
+    let job = if to_file {
+        Job::Whole
+    } else {
+        match ndless::msg::msg_3b(
+            "Tile Jobs",
+            "Split the render across several calculators",
+            "Whole image",
+            "One tile",
+            "Merge tiles",
+        ) {
+            Button::Two => {
+                let tile_input = ndless::msg::msg_2numeric(
+                    "Tile Job",
+                    "",
+                    "Tile to render",
+                    (1, 16),
+                    "Tiles in total",
+                    (1, 16),
+                );
+                if tile_input.is_none() {
+                    return;
+                }
+                let (tile, tiles) = tile_input.unwrap();
+
+                Job::Tile((tile as u16).min(tiles as u16), tiles as u16)
+            }
+            Button::Three => {
+                let tiles = ndless::msg::msg_numeric("Merge Tiles", "", "Tiles in total", (1, 16));
+                if tiles.is_none() {
+                    return;
+                }
+
+                Job::Merge(tiles.unwrap() as u16)
+            }
+            _ => Job::Whole,
+        }
+    };
+    let whole = matches!(job, Job::Whole);
 
     let lens_blur = ndless::msg::msg_2b(
         "Defocus Blur",
@@ -150,58 +388,387 @@ fn main() {
         "No",
     ) == Button::One;
 
+    let (operator, exposure) = ndless::msg::msg_2numeric(
+        "Tone Mapping",
+        "",
+        "Operator (1 clip, 2 Reinhard, 3 ACES, 4 filmic)",
+        (1, 4),
+        "Exposure (stops)",
+        (-8, 8),
+    )
+    .unwrap_or((1, 0));
+
+    let operator = match operator {
+        2 => ToneOperator::Reinhard,
+        3 => ToneOperator::AcesFit,
+        4 => ToneOperator::Filmic,
+        _ => ToneOperator::Clamp,
+    };
+
+    let filter = match ndless::msg::msg_numeric(
+        "Pixel Filter",
+        "",
+        "Filter (1 box, 2 tent, 3 gaussian, 4 Mitchell)",
+        (1, 4),
+    ) {
+        Some(2) => Filter::Tent,
+        Some(3) => Filter::Gaussian,
+        Some(4) => Filter::Mitchell,
+        _ => Filter::Box,
+    };
+
+    // Heatmaps get scaled to the most expensive pixel, so they need the whole image too
+    let mode = if !to_file && whole {
+        match ndless::msg::msg_3b(
+            "Render Mode",
+            "What should the image show",
+            "Color",
+            "Hit tests",
+            "Bounces",
+        ) {
+            Button::Two => RenderMode::HitTests,
+            Button::Three => RenderMode::Bounces,
+            _ => RenderMode::Color,
+        }
+    } else {
+        RenderMode::Color
+    };
+
+    // Both of these need the whole image in memory
+    let save_aovs = !to_file
+        && whole
+        && ndless::msg::msg_2b(
+            "Extra Outputs",
+            "Do you want to save depth, normal, albedo and object id buffers",
+            "Yes",
+            "No",
+        ) == Button::One;
+
+    let denoise = !to_file
+        && whole
+        && mode == RenderMode::Color
+        && ndless::msg::msg_2b(
+            "Denoise",
+            "Do you want to denoise the image after rendering",
+            "Yes",
+            "No",
+        ) == Button::One;
+
     unsafe {
         LOG_FILE = Some(BufWriter::new(File::create("nspray_log.txt.tns").unwrap()));
         START_TIME = Some(SystemTime::now());
     }
 
     dprintln!("Initializing... Selected iterations: {sample_count}, Defocus blur: {lens_blur}");
+    dprintln!("Time budget: {budget:?}");
+    dprintln!("Resolution: {width}x{height}, to file: {to_file}");
+    dprintln!("Tone mapping: {operator:?}, exposure: {exposure} stops, filter: {filter:?}");
+    dprintln!("Render mode: {mode:?}");
 
-    let apeture = if lens_blur { fxi32!(0.1) } else { fxi32!(0) };
-    let focus_dist = if lens_blur { fxi32!(10) } else { fxi32!(1) };
-
-    let camera = Camera::new(
-        Vec3FI32::new(fxi32!(2), fxi32!(6), fxi32!(6)),
-        Vec3FI32::new(fxi32!(0), fxi32!(0), fxi32!(0)),
-        Vec3FI32::new(fxi32!(0), fxi32!(1), fxi32!(0)),
-        fxi32!(45),
-        fxi32!(IMG_WIDTH as i32) / fxi32!(IMG_HEIGHT as i32),
-        apeture,
-        focus_dist,
-    );
+    let camera = gen_camera(width as u16, height as u16, lens_blur);
 
     let mut rand = Rand32::new(2);
-
-    let renderer = Renderer::new(
+    let tone_mapper = ToneMapper::new(operator, tonemap::stops(FixedI32::from(exposure)));
+
+    let mut aovs = if save_aovs {
+        Some(AovBuffers::new(
+            width as u16,
+            height as u16,
+            &[Aov::Depth, Aov::Normal, Aov::Albedo, Aov::ObjectId],
+        ))
+    } else if denoise {
+        Some(AovBuffers::new(
+            width as u16,
+            height as u16,
+            &[Aov::Depth, Aov::Normal, Aov::Albedo],
+        ))
+    } else {
+        None
+    };
+
+    let mut renderer = Renderer::new(
         camera,
         gen_scene(&mut rand),
-        IMG_WIDTH as u16,
-        IMG_HEIGHT as u16,
+        width as u16,
+        height as u16,
         sample_count as u16,
         lens_blur,
+        tone_mapper,
+        filter,
+        RENDER_SEED,
     );
+    renderer.set_mode(mode);
+
+    // Whole images just keep getting refined until the time runs out, but files and tiles have to
+    // know how many samples to take up front. Merging doesn't render anything at all.
+    let share = match job {
+        Job::Whole if to_file => Some(1),
+        Job::Tile(_, tiles) => Some(tiles as u32),
+        _ => None,
+    };
+    if let (Some(budget), Some(share)) = (budget, share) {
+        let pass_time = renderer.estimate_pass_time() / share;
+        let samples = (budget.as_millis() / pass_time.as_millis().max(1)).clamp(1, 1000) as u16;
+
+        dprintln!("One sample per pixel takes about {pass_time:?}, going with {samples} samples");
+
+        renderer.set_samples(samples);
+    }
 
-    init_screen();
-
-    renderer.render_scene(
-        &mut rand,
-        &mut screen_buff,
-        &mut rgb_buff,
-        &mut |buffer, _| {
-            blit_buffer(buffer);
-        },
-    );
+    let mut lcd_buff = vec![0u16; LCD_WIDTH * LCD_HEIGHT];
+    let mut paused = false;
+    let mut hud = Hud::new();
+    let mut stats = RenderStats::default();
+    // Camera rays per image row
+    let row_rays = width as u64 * renderer.samples() as u64;
 
-    dprintln!("Finished rendering");
+    init_screen();
 
-    dither::dither(&rgb_buff, &mut screen_buff);
+    // The finished image and the samples that went into it, for the pixel debugger
+    let mut passes = vec![0..renderer.samples()];
+    let image = if to_file {
+        let mut file = BufWriter::new(File::create("nspray_render.ppm.tns").unwrap());
+        // Kept apart from the LCD buffer so that the HUD doesn't get stuck in the preview
+        let mut preview_buff = vec![0u16; LCD_WIDTH * LCD_HEIGHT];
+
+        let finished = renderer
+            .render_to_ppm(&mut file, FILE_BAND_ROWS, &mut stats, &mut |row, rgb| {
+                // Nearest neighbour preview of the rows that make it onto the screen
+                for i in 0..LCD_HEIGHT {
+                    if i * height / LCD_HEIGHT != row as usize {
+                        continue;
+                    }
+
+                    for j in 0..LCD_WIDTH {
+                        let index = (j * width / LCD_WIDTH) * 3;
+                        let (r, g, b) = (
+                            rgb[index] as u16,
+                            rgb[index + 1] as u16,
+                            rgb[index + 2] as u16,
+                        );
+
+                        preview_buff[i * LCD_WIDTH + j] = (r >> 3) << 10 | (g >> 3) << 5 | b >> 3;
+                    }
+
+                    if i % 10 == 0 {
+                        let done = row as u64 + 1;
+
+                        lcd_buff.copy_from_slice(&preview_buff);
+                        hud.draw(&mut lcd_buff, done, height as u64, done * row_rays);
+                        blit_buffer(&mut lcd_buff);
+                    }
+                }
+
+                poll_keys(&mut paused, &mut hud)
+            })
+            .expect("Failed to save render");
+
+        file.flush().unwrap();
+
+        if finished {
+            dprintln!("Finished rendering to nspray_render.ppm.tns");
+        } else {
+            dprintln!("Render aborted, the rest of nspray_render.ppm.tns is black");
+        }
+
+        blit_buffer(&mut preview_buff);
+
+        None
+    } else {
+        let mut screen_buff = vec![0u16; width * height];
+        let mut rgb_buff = vec![0u8; width * height * 3];
+        let mut framebuffer = Framebuffer::new(width as u16, height as u16);
+
+        match job {
+            Job::Whole => {
+                let finished = if let Some(budget) = budget {
+                    let report = renderer.render_timed(
+                        budget,
+                        &mut framebuffer,
+                        &mut screen_buff,
+                        &mut rgb_buff,
+                        aovs.as_mut(),
+                        &mut stats,
+                        &mut |buffer, row, progress| {
+                            if row % 10 == 0 {
+                                // How many more passes fit in the budget only gets decided once
+                                // one ends, so the progress and ETA go to the end of this one
+                                let pass = &progress.pass;
+                                let done = pass.start as u64 * height as u64
+                                    + (row as u64 + 1) * (pass.end - pass.start) as u64;
+
+                                stretch_image(buffer, width, height, &mut lcd_buff, scaling);
+                                hud.draw(
+                                    &mut lcd_buff,
+                                    done,
+                                    pass.end as u64 * height as u64,
+                                    progress.rays,
+                                );
+                                blit_buffer(&mut lcd_buff);
+                            }
+
+                            poll_keys(&mut paused, &mut hud)
+                        },
+                    );
+
+                    dprintln!(
+                        "Got {} samples per pixel in {} passes, took {:?}",
+                        report.samples,
+                        report.passes.len(),
+                        report.elapsed
+                    );
+
+                    passes = report.passes;
+                    report.finished
+                } else {
+                    renderer.render_scene(
+                        &mut framebuffer,
+                        &mut screen_buff,
+                        &mut rgb_buff,
+                        aovs.as_mut(),
+                        &mut stats,
+                        &mut |buffer, row| {
+                            if row % 10 == 0 {
+                                let done = row as u64 + 1;
+
+                                stretch_image(buffer, width, height, &mut lcd_buff, scaling);
+                                hud.draw(&mut lcd_buff, done, height as u64, done * row_rays);
+                                blit_buffer(&mut lcd_buff);
+                            }
+
+                            poll_keys(&mut paused, &mut hud)
+                        },
+                    )
+                };
+
+                if finished {
+                    dprintln!("Finished rendering");
+                } else {
+                    dprintln!("Render aborted");
+                }
+
+                if mode != RenderMode::Color {
+                    let max = heatmap::resolve(&framebuffer, &mut screen_buff, &mut rgb_buff);
+
+                    dprintln!("Heatmap of {mode:?}, red is {max} per sample");
+                }
+            }
+            Job::Tile(tile, tiles) => {
+                let job = TileJob::bands(width as u16, height as u16, tiles)[tile as usize - 1];
+
+                dprintln!("Rendering tile {tile} of {tiles}: {:?}", job.rect);
+
+                let tile_buff = job.render(&renderer, &mut stats, &mut |tile_buff, row| {
+                    // Tiles are full width bands, so their rows line up with the image's
+                    let (_, top) = tile_buff.origin();
+                    let offset = top as usize * width;
+
+                    tile_buff.resolve_row(
+                        row - top,
+                        &tone_mapper,
+                        &mut screen_buff[offset..],
+                        &mut rgb_buff[offset * 3..],
+                    );
+
+                    if row % 10 == 0 {
+                        let done = (row + 1 - job.rect.y) as u64;
+
+                        stretch_image(&screen_buff, width, height, &mut lcd_buff, scaling);
+                        hud.draw(&mut lcd_buff, done, job.rect.height as u64, done * row_rays);
+                        blit_buffer(&mut lcd_buff);
+                    }
+
+                    poll_keys(&mut paused, &mut hud)
+                });
+
+                // An aborted tile doesn't get saved, but the preview is still there to look at
+                if let Some(tile_buff) = tile_buff {
+                    let mut file = BufWriter::new(File::create(tile_path(tile)).unwrap());
+                    tile_buff.write_to(&mut file).expect("Failed to save tile");
+                    file.flush().unwrap();
+
+                    dprintln!("Saved {}", tile_path(tile));
+
+                    framebuffer.merge(&tile_buff);
+                    framebuffer.resolve(&tone_mapper, &mut screen_buff, &mut rgb_buff);
+                } else {
+                    dprintln!("Tile aborted");
+                }
+            }
+            Job::Merge(tiles) => {
+                for tile in 1..=tiles {
+                    let mut file = File::open(tile_path(tile)).expect("Missing tile");
+                    let tile_buff = Framebuffer::read_from(&mut file).expect("Failed to read tile");
+
+                    framebuffer.merge(&tile_buff);
+
+                    dprintln!("Merged {}", tile_path(tile));
+                }
+
+                framebuffer.resolve(&tone_mapper, &mut screen_buff, &mut rgb_buff);
+
+                let mut file = BufWriter::new(File::create("nspray_merged.ppm.tns").unwrap());
+                export::write_ppm_header(&mut file, width, height).unwrap();
+                file.write_all(&rgb_buff).unwrap();
+                file.flush().unwrap();
+                framebuffer
+                    .export_pfm("nspray_merged.pfm.tns")
+                    .expect("Failed to save HDR image");
+            }
+        }
+
+        if denoise {
+            denoise::denoise(&mut rgb_buff, width, height, aovs.as_ref().unwrap(), 4);
+
+            dprintln!("Finished denoising");
+        }
+
+        dither::dither(&rgb_buff, &mut screen_buff, width, height);
+
+        dprintln!("Finished dithering");
+
+        // Aborted renders get saved too, with whatever made it into the image
+        if whole {
+            let mut file = BufWriter::new(File::create("nspray_render.ppm.tns").unwrap());
+            export::write_ppm_header(&mut file, width, height).unwrap();
+            file.write_all(&rgb_buff).expect("Failed to save render");
+            file.flush().unwrap();
+
+            dprintln!("Saved nspray_render.ppm.tns");
+        }
+
+        blit_image(&mut screen_buff, width, height, &mut lcd_buff, scaling);
+
+        if let Some(ref aovs) = aovs {
+            framebuffer
+                .export_pfm("nspray_color.pfm.tns")
+                .expect("Failed to save HDR image");
+            aovs.export("nspray").expect("Failed to save AOVs");
 
-    dprintln!("Finished dithering");
+            dprintln!("Saved AOVs");
+        }
 
-    blit_buffer(&mut screen_buff);
+        Some(screen_buff)
+    };
+
+    stats.log_report();
+    overflow::log_report();
 
     wait_key_pressed();
 
+    if let (Some(image), true) = (image, is_key_pressed(Key::D)) {
+        wait_no_key_pressed();
+        pixel_debugger(
+            &renderer,
+            &passes,
+            &image,
+            width,
+            height,
+            &mut lcd_buff,
+            scaling,
+        );
+    }
+
     dprintln!("Deinitializing screen...");
 
     deinit_screen();
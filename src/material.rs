@@ -1,33 +1,46 @@
 use oorandom::Rand32;
 
-use crate::{fixed::FixedI32, fxi32, hittable::HitRecord, ray::Ray, vec3::Vec3FI32};
+use crate::{
+    fixed::FixedI32, hittable::HitRecord, ray::Ray, scalar::Scalar, sync::ThreadSafe, vec3::Vec3,
+};
 
-fn reflect(v: Vec3FI32, n: Vec3FI32) -> Vec3FI32 {
-    v - n * v.dot(n) * fxi32!(2)
+fn reflect<T: Scalar>(v: Vec3<T>, n: Vec3<T>) -> Vec3<T> {
+    v - n * v.dot(n) * T::from_int(2)
 }
 
-pub trait Material {
-    fn scatter(&self, rand: &mut Rand32, ray: &Ray, record: &HitRecord) -> Option<(Ray, Vec3FI32)>;
+pub trait Material<T: Scalar = FixedI32>: ThreadSafe {
+    fn scatter(
+        &self,
+        rand: &mut Rand32,
+        ray: &Ray<T>,
+        record: &HitRecord<T>,
+    ) -> Option<(Ray<T>, Vec3<T>)>;
+
+    /// The base color of the surface at the hit point, without any lighting.
+    fn albedo(&self, record: &HitRecord<T>) -> Vec3<T>;
+
+    /// What the pixel debugger calls this material.
+    fn name(&self) -> &'static str;
 }
 
-pub struct Lambertian {
-    albedo: Vec3FI32,
+pub struct Lambertian<T = FixedI32> {
+    albedo: Vec3<T>,
 }
 
-impl Lambertian {
-    pub fn new(albedo: Vec3FI32) -> Self {
+impl<T: Scalar> Lambertian<T> {
+    pub fn new(albedo: Vec3<T>) -> Self {
         Self { albedo }
     }
 }
 
-impl Material for Lambertian {
+impl<T: Scalar> Material<T> for Lambertian<T> {
     fn scatter(
         &self,
         rand: &mut Rand32,
-        _ray: &Ray,
-        record: &HitRecord,
-    ) -> Option<(Ray, Vec3FI32)> {
-        let mut scattered_dir = record.normal + Vec3FI32::random_in_unit_sphere(rand);
+        _ray: &Ray<T>,
+        record: &HitRecord<T>,
+    ) -> Option<(Ray<T>, Vec3<T>)> {
+        let mut scattered_dir = record.normal + Vec3::random_in_unit_sphere(rand);
 
         if scattered_dir.near_zero() {
             scattered_dir = record.normal;
@@ -35,64 +48,97 @@ impl Material for Lambertian {
 
         Some((Ray::new(record.point, scattered_dir), self.albedo))
     }
+
+    fn albedo(&self, _record: &HitRecord<T>) -> Vec3<T> {
+        self.albedo
+    }
+
+    fn name(&self) -> &'static str {
+        "lambertian"
+    }
 }
 
-pub struct CheckeredLambertian {
-    albedo1: Vec3FI32,
-    albedo2: Vec3FI32,
+pub struct CheckeredLambertian<T = FixedI32> {
+    albedo1: Vec3<T>,
+    albedo2: Vec3<T>,
 }
 
-impl CheckeredLambertian {
-    pub fn new(albedo1: Vec3FI32, albedo2: Vec3FI32) -> Self {
+impl<T: Scalar> CheckeredLambertian<T> {
+    pub fn new(albedo1: Vec3<T>, albedo2: Vec3<T>) -> Self {
         Self { albedo1, albedo2 }
     }
 }
 
-impl Material for CheckeredLambertian {
-    fn scatter(&self, rand: &mut Rand32, ray: &Ray, record: &HitRecord) -> Option<(Ray, Vec3FI32)> {
-        let mut scattered_dir = record.normal + Vec3FI32::random_in_unit_sphere(rand);
+impl<T: Scalar> Material<T> for CheckeredLambertian<T> {
+    fn scatter(
+        &self,
+        rand: &mut Rand32,
+        _ray: &Ray<T>,
+        record: &HitRecord<T>,
+    ) -> Option<(Ray<T>, Vec3<T>)> {
+        let mut scattered_dir = record.normal + Vec3::random_in_unit_sphere(rand);
 
         if scattered_dir.near_zero() {
             scattered_dir = record.normal;
         }
 
-        let mut albedo = self.albedo1;
+        Some((Ray::new(record.point, scattered_dir), self.albedo(record)))
+    }
 
+    fn albedo(&self, record: &HitRecord<T>) -> Vec3<T> {
         if let Some(ref mapped) = record.mapped_point {
-            let x = mapped.x.modulo(fxi32!(2)).abs();
-            let y = mapped.y.modulo(fxi32!(2)).abs();
+            let two = T::from_int(2);
+            let x = mapped.x.modulo(two).abs();
+            let y = mapped.y.modulo(two).abs();
 
-            if (x > fxi32!(1) && y < fxi32!(1)) || (x < fxi32!(1) && y > fxi32!(1)) {
-                albedo = self.albedo2;
+            if (x > T::ONE && y < T::ONE) || (x < T::ONE && y > T::ONE) {
+                return self.albedo2;
             }
         }
 
-        Some((Ray::new(record.point, scattered_dir), albedo))
+        self.albedo1
+    }
+
+    fn name(&self) -> &'static str {
+        "checkered lambertian"
     }
 }
 
-pub struct Metal {
-    fuzziness: FixedI32,
-    albedo: Vec3FI32,
+pub struct Metal<T = FixedI32> {
+    fuzziness: T,
+    albedo: Vec3<T>,
 }
 
-impl Metal {
-    pub fn new(fuzziness: FixedI32, albedo: Vec3FI32) -> Self {
+impl<T: Scalar> Metal<T> {
+    pub fn new(fuzziness: T, albedo: Vec3<T>) -> Self {
         Self { fuzziness, albedo }
     }
 }
 
-impl Material for Metal {
-    fn scatter(&self, rand: &mut Rand32, ray: &Ray, record: &HitRecord) -> Option<(Ray, Vec3FI32)> {
-        let noise = Vec3FI32::random_in_unit_sphere(rand) * self.fuzziness;
+impl<T: Scalar> Material<T> for Metal<T> {
+    fn scatter(
+        &self,
+        rand: &mut Rand32,
+        ray: &Ray<T>,
+        record: &HitRecord<T>,
+    ) -> Option<(Ray<T>, Vec3<T>)> {
+        let noise = Vec3::random_in_unit_sphere(rand) * self.fuzziness;
 
         let reflected = reflect(ray.dir().unit_vector(), record.normal) + noise;
         let new_ray = Ray::new(record.point, reflected);
 
-        if new_ray.dir().dot(record.normal) > fxi32!(0) {
+        if new_ray.dir().dot(record.normal) > T::ZERO {
             Some((new_ray, self.albedo))
         } else {
             None
         }
     }
+
+    fn albedo(&self, _record: &HitRecord<T>) -> Vec3<T> {
+        self.albedo
+    }
+
+    fn name(&self) -> &'static str {
+        "metal"
+    }
 }
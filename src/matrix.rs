@@ -5,9 +5,7 @@ use core::{
 
 use alloc::fmt;
 
-use crate::{fixed::FixedI32, vec3::Vec3};
-
-pub type Matrix3x3FI32 = Matrix3x3<FixedI32>;
+use crate::vec3::Vec3;
 
 #[derive(Copy, Clone, Default)]
 pub struct Matrix3x3<T>
@@ -0,0 +1,91 @@
+#[cfg(feature = "std")]
+use core::sync::atomic::AtomicBool;
+use core::{
+    panic::Location,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use crate::dprintln;
+
+/// How many overflows get logged one by one before only counting them.
+const LOGGED: u32 = 8;
+
+// Atomic since the threaded renderer on the host can overflow on several threads at once. Nothing
+// else is ordered around it, so relaxed is enough.
+static COUNT: AtomicU32 = AtomicU32::new(0);
+
+// Held while logging, since the log file isn't safe to write to from several threads
+#[cfg(feature = "std")]
+static LOGGING: AtomicBool = AtomicBool::new(false);
+
+/// Runs `$checked` (the checked version of an operator) and records an overflow if it fails, but
+/// only with the `overflow_check` feature. Without it nothing gets checked and the fixed point
+/// operators just wrap like they always have.
+#[macro_export]
+macro_rules! overflow_check {
+    ($checked:expr, $op:expr) => {
+        if cfg!(feature = "overflow_check") && $checked.is_none() {
+            $crate::overflow::record($op, core::panic::Location::caller());
+        }
+    };
+}
+
+/// Counts an overflow of `op` at `location`, which is where the operator was used. That's often
+/// somewhere in vec3.rs, since that's where most of the math happens.
+pub fn record(op: &str, location: &Location) {
+    let count = increment();
+
+    if count <= LOGGED {
+        locked(|| {
+            dprintln!("Fixed point overflow in {op} at {location}");
+        });
+    } else if count == LOGGED + 1 {
+        locked(|| {
+            dprintln!("More fixed point overflows, only counting from now on");
+        });
+    }
+}
+
+/// Adds one to the count and returns the new one.
+#[cfg(feature = "std")]
+fn increment() -> u32 {
+    COUNT.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+// The calculator's ARMv5TE has no LDREX/STREX, so atomics there can only be loaded and stored. It
+// only ever runs one thread though, so that's all it takes.
+#[cfg(not(feature = "std"))]
+fn increment() -> u32 {
+    let count = COUNT.load(Ordering::Relaxed) + 1;
+    COUNT.store(count, Ordering::Relaxed);
+    count
+}
+
+/// Runs `log` with nothing else logging from another thread.
+#[cfg(feature = "std")]
+fn locked(log: impl FnOnce()) {
+    while LOGGING
+        .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        core::hint::spin_loop();
+    }
+
+    log();
+    LOGGING.store(false, Ordering::Release);
+}
+
+#[cfg(not(feature = "std"))]
+fn locked(log: impl FnOnce()) {
+    log();
+}
+
+pub fn count() -> u32 {
+    COUNT.load(Ordering::Relaxed)
+}
+
+pub fn log_report() {
+    if cfg!(feature = "overflow_check") {
+        dprintln!("Fixed point overflows: {}", count());
+    }
+}
@@ -1,14 +1,14 @@
 use core::fmt::{self, Display, Formatter};
 
-use crate::{fixed::FixedI32, vec3::Vec3FI32};
+use crate::{fixed::FixedI32, scalar::Scalar, vec3::Vec3};
 
 #[derive(Debug)]
-pub struct Ray {
-    origin: Vec3FI32,
-    dir: Vec3FI32,
+pub struct Ray<T = FixedI32> {
+    origin: Vec3<T>,
+    dir: Vec3<T>,
 }
 
-impl Display for Ray {
+impl<T: Display> Display for Ray<T> {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         formatter.write_fmt(format_args!(
             "[ origin: {}, dir: {} ]",
@@ -17,20 +17,20 @@ impl Display for Ray {
     }
 }
 
-impl Ray {
-    pub fn new(origin: Vec3FI32, dir: Vec3FI32) -> Ray {
+impl<T: Scalar> Ray<T> {
+    pub fn new(origin: Vec3<T>, dir: Vec3<T>) -> Self {
         Self { origin, dir }
     }
 
-    pub fn origin(&self) -> Vec3FI32 {
+    pub fn origin(&self) -> Vec3<T> {
         self.origin
     }
 
-    pub fn dir(&self) -> Vec3FI32 {
+    pub fn dir(&self) -> Vec3<T> {
         self.dir
     }
 
-    pub fn at(&self, t: FixedI32) -> Vec3FI32 {
+    pub fn at(&self, t: T) -> Vec3<T> {
         self.origin + self.dir * t
     }
 }
@@ -0,0 +1,245 @@
+use core::{
+    f32, f64,
+    fmt::{Debug, Display},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use ndless::prelude::Float;
+use oorandom::Rand32;
+
+use crate::{
+    fixed::FixedI32, fxi32, hittable, matrix::Matrix3x3, ray::Ray, sync::ThreadSafe, vec3::Vec3,
+};
+
+/// The number type the renderer does its math in. On the calculator that's always `FixedI32`,
+/// but everything from the camera to the materials works with any `Scalar`, so the host can render
+/// the same scene in f32 or f64 to see how far off the fixed point image is.
+pub trait Scalar:
+    Copy
+    + Default
+    + PartialOrd
+    + Display
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + ThreadSafe
+    + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const PI: Self;
+
+    fn from_fixed(value: FixedI32) -> Self;
+
+    /// Back to fixed point, saturating anything that doesn't fit.
+    fn to_fixed(self) -> FixedI32;
+
+    fn from_int(value: i32) -> Self;
+
+    fn sqrt(self) -> Self;
+
+    fn rsqrt(self) -> Self {
+        Self::ONE / self.sqrt()
+    }
+
+    fn abs(self) -> Self;
+
+    // Nothing in the renderer needs these two yet
+    #[allow(dead_code)]
+    fn sin(self) -> Self;
+
+    #[allow(dead_code)]
+    fn cos(self) -> Self;
+
+    fn tan(self) -> Self;
+
+    /// Remainder of dividing by `modulus`, with the sign of `self`.
+    fn modulo(self, modulus: Self) -> Self;
+
+    /// A random number in [0, 1).
+    fn rand(rand: &mut Rand32) -> Self;
+
+    // The rest are here so that FixedI32 can swap in Q32.32 versions with the `fixed64` feature
+
+    /// Where `ray` enters and leaves the sphere, if it hits it at all.
+    fn sphere_roots(center: Vec3<Self>, radius: Self, ray: &Ray<Self>) -> Option<(Self, Self)> {
+        hittable::sphere_roots(center, radius, ray)
+    }
+
+    /// How far along `ray` it hits the plane, unless it's (nearly) parallel to it.
+    fn plane_distance(center: Vec3<Self>, normal: Vec3<Self>, ray: &Ray<Self>) -> Option<Self> {
+        hittable::plane_distance(center, normal, ray)
+    }
+
+    fn invert_matrix(matrix: Matrix3x3<Self>) -> Matrix3x3<Self> {
+        matrix.invert()
+    }
+}
+
+impl Scalar for FixedI32 {
+    const ZERO: Self = fxi32!(0);
+    const ONE: Self = fxi32!(1);
+    const PI: Self = FixedI32::PI;
+
+    fn from_fixed(value: FixedI32) -> Self {
+        value
+    }
+
+    fn to_fixed(self) -> FixedI32 {
+        self
+    }
+
+    fn from_int(value: i32) -> Self {
+        FixedI32::from(value)
+    }
+
+    fn sqrt(self) -> Self {
+        FixedI32::sqrt(self)
+    }
+
+    fn rsqrt(self) -> Self {
+        FixedI32::rsqrt(self)
+    }
+
+    fn abs(self) -> Self {
+        FixedI32::abs(self)
+    }
+
+    fn sin(self) -> Self {
+        FixedI32::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        FixedI32::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        FixedI32::tan(self)
+    }
+
+    fn modulo(self, modulus: Self) -> Self {
+        FixedI32::modulo(self, modulus)
+    }
+
+    fn rand(rand: &mut Rand32) -> Self {
+        FixedI32::rand(rand)
+    }
+
+    #[cfg(feature = "fixed64")]
+    fn sphere_roots(center: Vec3<Self>, radius: Self, ray: &Ray<Self>) -> Option<(Self, Self)> {
+        hittable::sphere_roots_wide(center, radius, ray)
+    }
+
+    #[cfg(feature = "fixed64")]
+    fn plane_distance(center: Vec3<Self>, normal: Vec3<Self>, ray: &Ray<Self>) -> Option<Self> {
+        hittable::plane_distance_wide(center, normal, ray)
+    }
+
+    #[cfg(feature = "fixed64")]
+    fn invert_matrix(matrix: Matrix3x3<Self>) -> Matrix3x3<Self> {
+        hittable::invert_wide(matrix)
+    }
+}
+
+// Both float versions take the random numbers from the same bits as FixedI32::rand, so a float
+// render makes the same random choices a fixed point one does (as long as the math agrees on them)
+
+impl Scalar for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const PI: Self = f32::consts::PI;
+
+    fn from_fixed(value: FixedI32) -> Self {
+        value.to_f32()
+    }
+
+    fn to_fixed(self) -> FixedI32 {
+        // Float to int casts saturate
+        FixedI32::from_bits(Float::round(self * 65536.0) as i32)
+    }
+
+    fn from_int(value: i32) -> Self {
+        value as f32
+    }
+
+    fn sqrt(self) -> Self {
+        Float::sqrt(self)
+    }
+
+    fn abs(self) -> Self {
+        Float::abs(self)
+    }
+
+    fn sin(self) -> Self {
+        Float::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        Float::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        Float::tan(self)
+    }
+
+    fn modulo(self, modulus: Self) -> Self {
+        self % modulus
+    }
+
+    fn rand(rand: &mut Rand32) -> Self {
+        (rand.rand_u32() & 0xffff) as f32 / 65536.0
+    }
+}
+
+impl Scalar for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const PI: Self = f64::consts::PI;
+
+    fn from_fixed(value: FixedI32) -> Self {
+        value.to_bits() as f64 / 65536.0
+    }
+
+    fn to_fixed(self) -> FixedI32 {
+        FixedI32::from_bits(Float::round(self * 65536.0) as i32)
+    }
+
+    fn from_int(value: i32) -> Self {
+        value as f64
+    }
+
+    fn sqrt(self) -> Self {
+        Float::sqrt(self)
+    }
+
+    fn abs(self) -> Self {
+        Float::abs(self)
+    }
+
+    fn sin(self) -> Self {
+        Float::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        Float::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        Float::tan(self)
+    }
+
+    fn modulo(self, modulus: Self) -> Self {
+        self % modulus
+    }
+
+    fn rand(rand: &mut Rand32) -> Self {
+        (rand.rand_u32() & 0xffff) as f64 / 65536.0
+    }
+}
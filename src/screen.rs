@@ -5,6 +5,16 @@ use ndless_sys::{lcd_blit, lcd_init, scr_type_t};
 const SCR_320X240_555: scr_type_t = 5;
 const SCR_TYPE_INVALID: scr_type_t = 1;
 
+pub const LCD_WIDTH: usize = 320;
+pub const LCD_HEIGHT: usize = 240;
+
+/// How images that aren't the size of the LCD get stretched onto it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scaling {
+    Nearest,
+    Bilinear,
+}
+
 pub fn init_screen() {
     let res = unsafe { lcd_init(SCR_320X240_555) };
 
@@ -14,13 +24,48 @@ pub fn init_screen() {
 }
 
 pub fn blit_buffer(buffer: &mut [u16]) {
-    assert_eq!(buffer.len(), 320 * 240);
+    assert_eq!(buffer.len(), LCD_WIDTH * LCD_HEIGHT);
 
     unsafe {
         lcd_blit(buffer.as_mut_ptr() as *mut c_void, SCR_320X240_555);
     }
 }
 
+/// Shows a `width` x `height` RGB555 image, going through `lcd_buff` to stretch it to the LCD's
+/// size first if needed.
+pub fn blit_image(
+    image: &mut [u16],
+    width: usize,
+    height: usize,
+    lcd_buff: &mut [u16],
+    scaling: Scaling,
+) {
+    if width == LCD_WIDTH && height == LCD_HEIGHT {
+        blit_buffer(image);
+    } else {
+        stretch_image(image, width, height, lcd_buff, scaling);
+        blit_buffer(lcd_buff);
+    }
+}
+
+/// Puts a `width` x `height` RGB555 image into `lcd_buff`, stretched to the LCD's size, so that
+/// things can be drawn over it before it gets blitted.
+pub fn stretch_image(
+    image: &[u16],
+    width: usize,
+    height: usize,
+    lcd_buff: &mut [u16],
+    scaling: Scaling,
+) {
+    if width == LCD_WIDTH && height == LCD_HEIGHT {
+        lcd_buff.copy_from_slice(image);
+    } else {
+        scale(
+            image, width, height, lcd_buff, LCD_WIDTH, LCD_HEIGHT, scaling,
+        );
+    }
+}
+
 pub fn deinit_screen() {
     let res = unsafe { lcd_init(SCR_TYPE_INVALID) };
 
@@ -28,3 +73,57 @@ pub fn deinit_screen() {
         panic!("Failed to deinitialize screen");
     }
 }
+
+/// Resizes the RGB555 image `src` into `dst`. Works both ways, but there's no filtering when
+/// shrinking, so it's mostly meant for blowing small previews up to the LCD's size.
+pub fn scale(
+    src: &[u16],
+    src_width: usize,
+    src_height: usize,
+    dst: &mut [u16],
+    dst_width: usize,
+    dst_height: usize,
+    scaling: Scaling,
+) {
+    assert_eq!(src.len(), src_width * src_height);
+    assert_eq!(dst.len(), dst_width * dst_height);
+
+    for i in 0..dst_height {
+        for j in 0..dst_width {
+            dst[i * dst_width + j] = match scaling {
+                Scaling::Nearest => {
+                    src[(i * src_height / dst_height) * src_width + j * src_width / dst_width]
+                }
+                Scaling::Bilinear => {
+                    // Source coordinates of this pixel's center, with 8 fractional bits
+                    let y = (((2 * i + 1) * src_height * 128) / dst_height).saturating_sub(128);
+                    let x = (((2 * j + 1) * src_width * 128) / dst_width).saturating_sub(128);
+
+                    let (y0, x0) = ((y >> 8).min(src_height - 1), (x >> 8).min(src_width - 1));
+                    let (y1, x1) = ((y0 + 1).min(src_height - 1), (x0 + 1).min(src_width - 1));
+                    let (fy, fx) = ((y & 0xff) as u32, (x & 0xff) as u32);
+
+                    let top = lerp_rgb555(src[y0 * src_width + x0], src[y0 * src_width + x1], fx);
+                    let bottom =
+                        lerp_rgb555(src[y1 * src_width + x0], src[y1 * src_width + x1], fx);
+
+                    lerp_rgb555(top, bottom, fy)
+                }
+            };
+        }
+    }
+}
+
+/// Blends two RGB555 colors channel by channel, `t` going from 0 (all `a`) to 256 (all `b`).
+fn lerp_rgb555(a: u16, b: u16, t: u32) -> u16 {
+    let mut out = 0;
+
+    for shift in [10, 5, 0].iter() {
+        let ca = ((a >> shift) & 0x1f) as u32;
+        let cb = ((b >> shift) & 0x1f) as u32;
+
+        out |= (((ca * (256 - t) + cb * t + 128) >> 8) as u16) << shift;
+    }
+
+    out
+}
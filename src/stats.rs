@@ -0,0 +1,105 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::dprintln;
+
+/// The kinds of objects that get intersection tests counted separately.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+    Sphere,
+    Plane,
+    Other,
+}
+
+/// Counts of what the renderer spent its time on, for working out what's slow on the calculator
+/// without a profiler. Counters are only ever added to, so one of these can be kept across
+/// several renders (or passes) and stats from different threads can be merged.
+#[derive(Clone, Debug, Default)]
+pub struct RenderStats {
+    pub primary_rays: u64,
+    pub bounce_rays: u64,
+    /// Intersection tests, indexed by `Primitive`.
+    pub tests: [u64; 3],
+    pub scatters: u64,
+    /// Rays a material soaked up instead of scattering.
+    pub absorbed: u64,
+    /// Rays that were still bouncing when the bounce limit ran out.
+    pub bounce_limit: u64,
+    /// Time spent on every image row, summed over all the passes. The calculator's clock only
+    /// ticks once a second, so these only really mean something there for slow rows.
+    pub row_times: Vec<Duration>,
+}
+
+impl RenderStats {
+    pub fn count_test(&mut self, primitive: Primitive) {
+        self.tests[primitive as usize] += 1;
+    }
+
+    pub fn add_row_time(&mut self, row: u16, time: Duration) {
+        let row = row as usize;
+
+        if self.row_times.len() <= row {
+            self.row_times.resize(row + 1, Duration::default());
+        }
+
+        self.row_times[row] += time;
+    }
+
+    // Only the threaded renderer has more than one of these going at a time
+    #[cfg(feature = "std")]
+    pub fn merge(&mut self, other: &RenderStats) {
+        self.primary_rays += other.primary_rays;
+        self.bounce_rays += other.bounce_rays;
+        for (tests, other) in self.tests.iter_mut().zip(other.tests.iter()) {
+            *tests += other;
+        }
+        self.scatters += other.scatters;
+        self.absorbed += other.absorbed;
+        self.bounce_limit += other.bounce_limit;
+
+        for (row, time) in other.row_times.iter().enumerate() {
+            self.add_row_time(row as u16, *time);
+        }
+    }
+
+    /// Writes a summary table to the log.
+    pub fn log_report(&self) {
+        dprintln!("Render stats:");
+        dprintln!("  primary rays       {:>12}", self.primary_rays);
+        dprintln!("  bounce rays        {:>12}", self.bounce_rays);
+
+        dprintln!(
+            "  sphere tests       {:>12}",
+            self.tests[Primitive::Sphere as usize]
+        );
+        dprintln!(
+            "  plane tests        {:>12}",
+            self.tests[Primitive::Plane as usize]
+        );
+        dprintln!(
+            "  other tests        {:>12}",
+            self.tests[Primitive::Other as usize]
+        );
+
+        dprintln!("  scatter calls      {:>12}", self.scatters);
+        dprintln!("  absorbed rays      {:>12}", self.absorbed);
+        dprintln!("  hit bounce limit   {:>12}", self.bounce_limit);
+
+        let rows = self.row_times.len() as u32;
+        if rows == 0 {
+            return;
+        }
+
+        let total: Duration = self.row_times.iter().sum();
+        let (slowest, slowest_time) = self
+            .row_times
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, time)| **time)
+            .unwrap();
+
+        dprintln!("  time in rows       {:>12?}", total);
+        dprintln!("  per row            {:>12?}", total / rows);
+        dprintln!("  slowest row        {:>12} ({slowest_time:?})", slowest);
+    }
+}
@@ -0,0 +1,18 @@
+// The calculator only ever has one thread, so scenes get shared with plain `Rc`s there. With the
+// `std` feature they have to be `Send + Sync` instead, so that the host can render on every core.
+
+#[cfg(not(feature = "std"))]
+pub use alloc::rc::Rc as Shared;
+#[cfg(feature = "std")]
+pub use alloc::sync::Arc as Shared;
+
+/// `Send + Sync` with the `std` feature, nothing at all without it.
+#[cfg(feature = "std")]
+pub trait ThreadSafe: Send + Sync {}
+#[cfg(feature = "std")]
+impl<T: Send + Sync + ?Sized> ThreadSafe for T {}
+
+#[cfg(not(feature = "std"))]
+pub trait ThreadSafe {}
+#[cfg(not(feature = "std"))]
+impl<T: ?Sized> ThreadSafe for T {}
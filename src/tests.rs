@@ -1,2 +1,22 @@
 #[cfg(test)]
+pub mod aov;
+#[cfg(test)]
+pub mod caster;
+#[cfg(test)]
+pub mod denoise;
+#[cfg(test)]
+pub mod exp;
+#[cfg(test)]
 pub mod fixed;
+#[cfg(all(test, feature = "fixed64"))]
+pub mod fixed64;
+#[cfg(test)]
+pub mod framebuffer;
+#[cfg(test)]
+pub mod heatmap;
+#[cfg(all(test, feature = "std"))]
+pub mod threaded;
+#[cfg(test)]
+pub mod tonemap;
+#[cfg(test)]
+pub mod trig;
@@ -0,0 +1,72 @@
+use alloc::vec::Vec;
+
+use super::caster::test_renderer;
+use crate::{
+    aov::{Aov, AovBuffers, NO_OBJECT},
+    caster::RenderControl,
+    export::{vec3_channel, write_pfm, write_pgm16},
+    filter::Filter,
+    framebuffer::Framebuffer,
+    fxi32,
+    stats::RenderStats,
+    tile::Rect,
+    vec3::Vec3FI32,
+};
+
+#[test]
+fn first_hits() {
+    let renderer = test_renderer(Filter::Box);
+    let mut framebuffer = Framebuffer::new(24, 18);
+    let mut aovs = AovBuffers::new(24, 18, &[Aov::Depth, Aov::Normal, Aov::ObjectId]);
+
+    renderer.render_region(
+        Rect::new(0, 0, 24, 18),
+        &mut framebuffer,
+        Some(&mut aovs),
+        &mut RenderStats::default(),
+        &mut |_, _| RenderControl::Continue,
+    );
+
+    assert!(aovs.albedo.is_none());
+    let depth = aovs.depth.as_ref().unwrap();
+    let normal = aovs.normal.as_ref().unwrap();
+    let object_id = aovs.object_id.as_ref().unwrap();
+
+    // The middle of the image looks straight at the front of the small sphere, half a unit away
+    let center = 9 * 24 + 12;
+    assert!(depth[center] > fxi32!(0.5) && depth[center] < fxi32!(0.52));
+    assert!(normal[center].z > fxi32!(0.95));
+    assert_eq!(object_id[center], 1);
+
+    // The bottom row only sees the big one
+    assert_eq!(object_id[17 * 24 + 12], 0);
+    assert!(normal[17 * 24 + 12].y > fxi32!(0.9));
+
+    // And the top row sees nothing at all
+    assert_eq!(object_id[12], NO_OBJECT);
+    assert_eq!(normal[12], Vec3FI32::default());
+    assert!(depth[12] > fxi32!(40));
+}
+
+#[test]
+fn export_headers() {
+    let aovs = AovBuffers::new(3, 2, &[Aov::Depth, Aov::Normal, Aov::ObjectId]);
+
+    let mut depth = Vec::new();
+    write_pfm(&mut depth, 3, 2, 1, |i, _| aovs.depth.as_ref().unwrap()[i]).unwrap();
+    assert!(depth.starts_with(b"Pf\n3 2\n-1.0\n"));
+    assert_eq!(depth.len(), 12 + 3 * 2 * 4);
+
+    let mut normal = Vec::new();
+    write_pfm(&mut normal, 3, 2, 3, |i, c| {
+        vec3_channel(aovs.normal.as_ref().unwrap()[i], c)
+    })
+    .unwrap();
+    assert!(normal.starts_with(b"PF\n3 2\n-1.0\n"));
+    assert_eq!(normal.len(), 12 + 3 * 2 * 3 * 4);
+
+    let mut ids = Vec::new();
+    write_pgm16(&mut ids, 3, 2, aovs.object_id.as_ref().unwrap()).unwrap();
+    assert!(ids.starts_with(b"P5\n3 2\n65535\n"));
+    assert_eq!(&ids[ids.len() - 2..], &NO_OBJECT.to_be_bytes());
+}
@@ -0,0 +1,246 @@
+use alloc::{boxed::Box, vec};
+use core::time::Duration;
+use oorandom::Rand32;
+
+#[cfg(feature = "std")]
+use crate::compare::ImageDiff;
+use crate::{
+    camera::Camera,
+    caster::{sample_rng, RenderControl, Renderer},
+    filter::Filter,
+    fixed::FixedI32,
+    framebuffer::Framebuffer,
+    fxi32,
+    hittable::{HittableList, Sphere},
+    material::{Lambertian, Metal},
+    scalar::Scalar,
+    stats::{Primitive, RenderStats},
+    sync::Shared,
+    tile::Rect,
+    tonemap::ToneMapper,
+    vec3::Vec3FI32,
+};
+
+pub fn test_renderer(filter: Filter) -> Renderer {
+    let scene = HittableList::new(vec![
+        Box::new(Sphere::new(
+            Vec3FI32::new(fxi32!(0), fxi32!(-100), fxi32!(-1)),
+            fxi32!(99.5),
+            Some(Shared::new(Lambertian::new(Vec3FI32::from(fxi32!(0.5))))),
+        )),
+        Box::new(Sphere::new(
+            Vec3FI32::new(fxi32!(0), fxi32!(0), fxi32!(-1)),
+            fxi32!(0.5),
+            Some(Shared::new(Metal::new(
+                fxi32!(0.3),
+                Vec3FI32::from(fxi32!(0.8)),
+            ))),
+        )),
+    ]);
+    let camera = Camera::new(
+        Vec3FI32::default(),
+        Vec3FI32::new(fxi32!(0), fxi32!(0), fxi32!(-1)),
+        Vec3FI32::new(fxi32!(0), fxi32!(1), fxi32!(0)),
+        fxi32!(45),
+        fxi32!(4) / 3,
+        fxi32!(0),
+        fxi32!(1),
+    );
+
+    Renderer::new(
+        camera,
+        scene,
+        24,
+        18,
+        4,
+        false,
+        ToneMapper::default(),
+        filter,
+        7,
+    )
+}
+
+/// The scene and camera that the calculator renders, in any `Scalar`.
+pub fn main_renderer<T: Scalar>(width: u16, height: u16, samples: u16) -> Renderer<T> {
+    Renderer::new(
+        crate::gen_camera(width, height, false),
+        crate::gen_scene(&mut Rand32::new(2)),
+        width,
+        height,
+        samples,
+        false,
+        ToneMapper::default(),
+        Filter::Mitchell,
+        crate::RENDER_SEED,
+    )
+}
+
+/// Renders the whole image in one go.
+pub fn render_all<T: Scalar>(renderer: &Renderer<T>) -> Framebuffer {
+    let mut framebuffer = Framebuffer::new(renderer.width(), renderer.height());
+
+    renderer.render_region(
+        Rect::new(0, 0, renderer.width(), renderer.height()),
+        &mut framebuffer,
+        None,
+        &mut RenderStats::default(),
+        &mut |_, _| RenderControl::Continue,
+    );
+
+    framebuffer
+}
+
+#[test]
+fn crop_matches_full_render() {
+    let renderer = test_renderer(Filter::Tent);
+    let mut full = Framebuffer::new(24, 18);
+    let mut crop = Framebuffer::new(24, 18);
+
+    renderer.render_region(
+        Rect::new(0, 0, 24, 18),
+        &mut full,
+        None,
+        &mut RenderStats::default(),
+        &mut |_, _| RenderControl::Continue,
+    );
+    renderer.render_region(
+        Rect::new(5, 4, 10, 8),
+        &mut crop,
+        None,
+        &mut RenderStats::default(),
+        &mut |_, _| RenderControl::Continue,
+    );
+
+    // Away from the crop's border no samples from outside of it can land
+    for y in 6..10 {
+        for x in 7..13 {
+            assert_eq!(crop.pixel(y * 24 + x), full.pixel(y * 24 + x));
+        }
+    }
+}
+
+#[test]
+fn sample_seeds() {
+    let first = |seed, x, y, sample| sample_rng(seed, x, y, sample).rand_u32();
+
+    assert_eq!(first(7, 3, 4, 0), first(7, 3, 4, 0));
+
+    // Neighbours in any direction, the next sample and another seed all get their own streams
+    let rolls = [
+        first(7, 3, 4, 0),
+        first(7, 4, 4, 0),
+        first(7, 3, 5, 0),
+        first(7, 4, 3, 0),
+        first(7, 3, 4, 1),
+        first(8, 3, 4, 0),
+    ];
+    for (i, a) in rolls.iter().enumerate() {
+        for b in rolls[i + 1..].iter() {
+            assert_ne!(a, b);
+        }
+    }
+}
+
+#[test]
+fn pause_then_abort() {
+    let renderer = test_renderer(Filter::Box);
+    let mut framebuffer = Framebuffer::new(24, 18);
+    let mut calls = 0;
+
+    // Pauses twice after the first row, then gives up
+    let finished = renderer.render_region(
+        Rect::new(0, 0, 24, 18),
+        &mut framebuffer,
+        None,
+        &mut RenderStats::default(),
+        &mut |_, _| {
+            calls += 1;
+            match calls {
+                1 | 2 => RenderControl::Pause,
+                _ => RenderControl::Abort,
+            }
+        },
+    );
+
+    assert!(!finished);
+    assert_eq!(calls, 3);
+    assert_ne!(framebuffer.pixel(0), Vec3FI32::default());
+    assert_eq!(framebuffer.pixel(24), Vec3FI32::default());
+}
+
+#[test]
+fn timed_render_always_takes_a_pass() {
+    let renderer = test_renderer(Filter::Box);
+    let mut framebuffer = Framebuffer::new(24, 18);
+    let mut screen_buff = vec![0u16; 24 * 18];
+    let mut rgb_buff = vec![0u8; 24 * 18 * 3];
+
+    let report = renderer.render_timed(
+        Duration::default(),
+        &mut framebuffer,
+        &mut screen_buff,
+        &mut rgb_buff,
+        None,
+        &mut RenderStats::default(),
+        &mut |_, _, progress| {
+            assert_eq!(progress.pass, 0..1);
+            RenderControl::Continue
+        },
+    );
+
+    assert!(report.finished);
+    assert_eq!(report.samples, 1);
+    assert_eq!(report.passes, vec![0..1]);
+    assert_ne!(framebuffer.pixel(0), Vec3FI32::default());
+}
+
+#[test]
+fn counts_rays() {
+    let renderer = test_renderer(Filter::Box);
+    let mut framebuffer = Framebuffer::new(24, 18);
+    let mut stats = RenderStats::default();
+
+    renderer.render_region(
+        Rect::new(0, 0, 24, 18),
+        &mut framebuffer,
+        None,
+        &mut stats,
+        &mut |_, _| RenderControl::Continue,
+    );
+
+    assert_eq!(stats.primary_rays, 24 * 18 * 4);
+    assert_eq!(stats.row_times.len(), 18);
+    // Every ray gets tested against both spheres
+    assert_eq!(
+        stats.tests[Primitive::Sphere as usize],
+        2 * (stats.primary_rays + stats.bounce_rays)
+    );
+    assert_eq!(stats.scatters, stats.bounce_rays + stats.absorbed);
+}
+
+#[test]
+fn floats_agree_with_fixed_point() {
+    let fixed = render_all(&main_renderer::<FixedI32>(64, 48, 4));
+    let single = render_all(&main_renderer::<f32>(64, 48, 4));
+    let double = render_all(&main_renderer::<f64>(64, 48, 4));
+
+    let mean_error = |a: &Framebuffer, b: &Framebuffer| {
+        let mut total = 0.0;
+        for i in 0..64 * 48 {
+            let diff = a.pixel(i) - b.pixel(i);
+            total += diff.x.abs().to_f32() + diff.y.abs().to_f32() + diff.z.abs().to_f32();
+        }
+
+        total / (64 * 48 * 3) as f32
+    };
+
+    // A few paths take different random turns, but on average the images are the same
+    assert!(mean_error(&fixed, &double) < 0.005);
+    // And on screen too
+    #[cfg(feature = "std")]
+    {
+        let diff = ImageDiff::new(&double, &fixed, &ToneMapper::default());
+        assert!(diff.psnr() > 40.0, "{}", diff);
+    }
+    assert!(mean_error(&single, &double) < 0.00001);
+}
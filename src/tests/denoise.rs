@@ -0,0 +1,73 @@
+use alloc::vec::Vec;
+
+use crate::{
+    aov::{Aov, AovBuffers},
+    denoise::denoise,
+    fxi32,
+    vec3::Vec3FI32,
+};
+
+const W: usize = 16;
+const H: usize = 16;
+
+/// Left half faces +z, right half faces +x, everything at the same depth.
+fn two_faces() -> AovBuffers {
+    let mut aovs = AovBuffers::new(W as u16, H as u16, &[Aov::Depth, Aov::Normal]);
+
+    for i in 0..H {
+        for j in 0..W {
+            aovs.depth.as_mut().unwrap()[i * W + j] = fxi32!(5);
+            aovs.normal.as_mut().unwrap()[i * W + j] = if j < W / 2 {
+                Vec3FI32::new(fxi32!(0), fxi32!(0), fxi32!(1))
+            } else {
+                Vec3FI32::new(fxi32!(1), fxi32!(0), fxi32!(0))
+            };
+        }
+    }
+
+    aovs
+}
+
+fn spread(rgb: &[u8]) -> i32 {
+    let min = *rgb.iter().min().unwrap() as i32;
+    let max = *rgb.iter().max().unwrap() as i32;
+
+    max - min
+}
+
+#[test]
+fn smooths_noise() {
+    let aovs = two_faces();
+    let mut rgb: Vec<u8> = (0..W * H * 3)
+        .map(|i| {
+            if (i / 3 + i / (3 * W)) % 2 == 0 {
+                120
+            } else {
+                136
+            }
+        })
+        .collect();
+
+    denoise(&mut rgb, W, H, &aovs, 3);
+
+    let left: Vec<u8> = (0..H)
+        .flat_map(|i| rgb[i * W * 3..(i * W + W / 2) * 3].iter().copied())
+        .collect();
+
+    assert!(spread(&left) < 16);
+}
+
+#[test]
+fn keeps_normal_edges() {
+    let aovs = two_faces();
+    let mut rgb: Vec<u8> = (0..W * H * 3)
+        .map(|i| if (i / 3) % W < W / 2 { 40 } else { 220 })
+        .collect();
+
+    denoise(&mut rgb, W, H, &aovs, 4);
+
+    for i in 0..H {
+        assert_eq!(rgb[(i * W + W / 2 - 1) * 3], 40);
+        assert_eq!(rgb[(i * W + W / 2) * 3], 220);
+    }
+}
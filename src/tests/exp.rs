@@ -0,0 +1,38 @@
+use fixed::FixedI32;
+
+use crate::fxi32;
+
+/// How far `actual` (Q16.16) is from `exact`, in steps of 2^-16.
+fn steps(actual: FixedI32, exact: f64) -> f64 {
+    (actual.to_bits() as f64 - exact * 65536.0).abs()
+}
+
+fn exact(value: FixedI32) -> f64 {
+    value.to_bits() as f64 / 65536.0
+}
+
+#[test]
+fn exp_and_exp2() {
+    for i in -1200..=1000 {
+        let x = FixedI32::from_bits(i * 0x1_0000 / 100 + 7);
+
+        let e = exact(x).exp();
+        assert!(
+            steps(x.exp(), e) <= 0.5 + e * 65536.0 / (1 << 26) as f64,
+            "exp({x}) = {}",
+            x.exp()
+        );
+
+        let e = exact(x).exp2();
+        assert!(
+            steps(x.exp2(), e) <= 0.5 + e * 65536.0 / (1 << 26) as f64,
+            "exp2({x}) = {}",
+            x.exp2()
+        );
+    }
+
+    // Saturates instead of wrapping around
+    assert_eq!(fxi32!(11).exp(), FixedI32::from_bits(i32::MAX));
+    assert_eq!(fxi32!(-30).exp(), fxi32!(0));
+    assert_eq!(fxi32!(10).exp2(), fxi32!(1024));
+}
@@ -1,6 +1,7 @@
-use fixed::FixedI32;
+use alloc::format;
+use fixed::{Fixed, FixedI32, ParseFixedError};
 
-use crate::{dprintln, fixed};
+use crate::{dprintln, fixed, fxi32};
 
 #[test]
 fn mul() {
@@ -50,8 +51,162 @@ fn from_str() {
 
     assert_eq!(c1, c2);
 
+    // .47215 is 30942.8 / 65536, which rounds up
     let c1 = FixedI32::from("3921.47215");
-    let c2 = FixedI32::from_components(0b0000111101010001, 0b0111100011011110);
+    let c2 = FixedI32::from_components(0b0000111101010001, 0b0111100011011111);
 
     assert_eq!(c1, c2);
+
+    let c1 = FixedI32::from("-0.5");
+    let c2 = FixedI32::from_bits(-0x8000);
+
+    assert_eq!(c1, c2);
+}
+
+#[test]
+fn parse_errors_and_exponents() {
+    assert_eq!("1.5e-3".parse(), Ok(FixedI32::from_bits(98)));
+    assert_eq!("+2E4".parse(), Ok(fxi32!(20000)));
+    assert_eq!(".25".parse(), Ok(fxi32!(0.25)));
+    assert_eq!("-32768".parse(), Ok(FixedI32::from_bits(i32::MIN)));
+    assert_eq!(
+        "0.000000000000000000000000000000000001".parse(),
+        Ok(fxi32!(0))
+    );
+    assert_eq!(
+        "12000000000000000000000000000000000e-30".parse(),
+        Ok(fxi32!(12000))
+    );
+
+    assert_eq!("".parse::<FixedI32>(), Err(ParseFixedError::Empty));
+    assert_eq!("-".parse::<FixedI32>(), Err(ParseFixedError::Empty));
+    assert_eq!(
+        "1.2.3".parse::<FixedI32>(),
+        Err(ParseFixedError::InvalidDigit)
+    );
+    assert_eq!("1e".parse::<FixedI32>(), Err(ParseFixedError::InvalidDigit));
+    assert_eq!(
+        "0x10".parse::<FixedI32>(),
+        Err(ParseFixedError::InvalidDigit)
+    );
+    assert_eq!(
+        "32768".parse::<FixedI32>(),
+        Err(ParseFixedError::OutOfRange)
+    );
+    assert_eq!("1e40".parse::<FixedI32>(), Err(ParseFixedError::OutOfRange));
+    assert_eq!("200".parse::<Fixed<24>>(), Err(ParseFixedError::OutOfRange));
+}
+
+#[test]
+fn literals() {
+    const HALF: FixedI32 = fxi32!(0.5);
+    static LIMITS: [FixedI32; 2] = [fxi32!(-32768), fxi32!("32767.99999")];
+
+    assert_eq!(HALF, FixedI32::from_bits(0x8000));
+    assert_eq!(fxi32!(-2.5), "-2.5".parse().unwrap());
+    assert_eq!(fxi32!("1e-2"), "0.01".parse().unwrap());
+    assert_eq!(fxi32!(0.95), FixedI32::from_bits(62259));
+    assert_eq!(LIMITS[0], FixedI32::from_bits(i32::MIN));
+    assert_eq!(LIMITS[1], FixedI32::from_bits(i32::MAX));
+}
+
+#[test]
+fn display() {
+    assert_eq!(format!("{}", fxi32!(3)), "3");
+    assert_eq!(format!("{}", fxi32!(-0.25)), "-0.25000");
+    assert_eq!(format!("{:.2}", fxi32!("1.996")), "2.00");
+    assert_eq!(format!("{:.0}", fxi32!("-9.5")), "-10");
+    assert_eq!(format!("{:>8.1}", fxi32!(0.75)), "     0.8");
+    assert_eq!(format!("{}", FixedI32::from_bits(i32::MIN)), "-32768");
+    assert_eq!(format!("{:.8}", Fixed::<24>::from("0.1")), "0.10000002");
+}
+
+#[test]
+fn same_math_in_every_format() {
+    let (a, b) = ("2.5", "0.75");
+
+    assert_eq!(
+        (FixedI32::from(a) * FixedI32::from(b)).to_f32(),
+        (Fixed::<24>::from(a) * Fixed::<24>::from(b)).to_f32()
+    );
+    // Both round down, just to different steps
+    assert_eq!(
+        (Fixed::<8>::from(a) / Fixed::<8>::from(b)).to_bits(),
+        (FixedI32::from(a) / FixedI32::from(b)).to_bits() >> 8
+    );
+    assert!((Fixed::<24>::PI.sin().to_f32()).abs() < 0.001);
+
+    // Rounded to nearest in every format that it fits in
+    assert_eq!(FixedI32::PI.to_bits(), 205887);
+    assert_eq!(Fixed::<2>::PI.to_bits(), 13);
+    assert_eq!(Fixed::<29>::PI.to_bits(), 0x6487_ed51);
+    assert_eq!(Fixed::<30>::PI.to_bits(), i32::MAX);
+}
+
+#[test]
+fn checked_and_saturating() {
+    let big = FixedI32::from(30000);
+    let max = FixedI32::from_bits(i32::MAX);
+    let min = FixedI32::from_bits(i32::MIN);
+
+    assert_eq!(big.checked_add(big), None);
+    assert_eq!(big.checked_sub(-big), None);
+    assert_eq!(big.checked_mul(fxi32!(2)), None);
+    assert_eq!(big.checked_div(fxi32!(0.5)), None);
+    assert_eq!(big.checked_div(fxi32!(0)), None);
+    assert_eq!(big.checked_mul_int(2), None);
+    assert_eq!(FixedI32::checked_from_int(40000), None);
+    assert_eq!(big.checked_mul(fxi32!(0.5)), Some(fxi32!(15000)));
+
+    assert_eq!(big.saturating_add(big), max);
+    assert_eq!((-big).saturating_sub(big), min);
+    assert_eq!((-big).saturating_mul(fxi32!(2)), min);
+    assert_eq!(big.saturating_div(fxi32!(0)), max);
+    assert_eq!((-big).saturating_div(fxi32!(0)), min);
+    assert_eq!(big.saturating_mul_int(-3), min);
+    assert_eq!(fxi32!(3).saturating_div(fxi32!(2)), fxi32!(1.5));
+}
+
+#[test]
+fn sqrt_matches_f64() {
+    let mut rng = oorandom::Rand32::new(3);
+
+    for i in 0..2000 {
+        // Half spread over the whole range, half small numbers
+        let bits = if i % 2 == 0 {
+            rng.rand_u32() >> 1
+        } else {
+            rng.rand_u32() >> 12
+        } as i32;
+        let x = FixedI32::from_bits(bits.max(1));
+        let exact = (x.to_bits() as f64 / 65536.0).sqrt() * 65536.0;
+
+        let root = x.sqrt().to_bits() as f64;
+        assert!(
+            (root - exact).abs() <= 0.5,
+            "sqrt({}) = {}, not {}",
+            x,
+            root,
+            exact
+        );
+
+        // rsqrt's result has to fit, so only check the part of the range where it does
+        if x >= FixedI32::from_bits(2) {
+            let exact = 65536.0 / (x.to_bits() as f64 / 65536.0).sqrt();
+            let rroot = x.rsqrt().to_bits() as f64;
+            assert!(
+                (rroot - exact).abs() <= 0.5 + exact / (1 << 18) as f64,
+                "rsqrt({}) = {}, not {}",
+                x,
+                rroot,
+                exact
+            );
+        }
+    }
+
+    assert_eq!(fxi32!(-4).sqrt(), fxi32!(0));
+    assert_eq!(fxi32!(4).rsqrt(), fxi32!(0.5));
+    // Q8.24 has more bits than rsqrt is good for
+    assert!((Fixed::<24>::from(4).rsqrt().to_f32() - 0.5).abs() < 1.0 / (1 << 18) as f32);
+    assert_eq!(Fixed::<8>::from(65536).sqrt(), Fixed::<8>::from(256));
 }
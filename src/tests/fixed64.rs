@@ -0,0 +1,74 @@
+use alloc::format;
+use fixed::FixedI32;
+use fixed64::FixedI64;
+use hittable::{HitRecord, Hittable, Sphere};
+use ray::Ray;
+use vec3::Vec3FI32;
+
+use crate::fxi32;
+
+#[test]
+fn mul_div_narrow() {
+    let a = FixedI64::from(fxi32!("1.5"));
+    let b = FixedI64::from(40000);
+
+    // 60000 doesn't fit in Q16.16, but goes back in fine once divided again
+    assert_eq!(a * b, FixedI64::from_bits(60000 << 32));
+    assert_eq!((a * b / b).narrow(), fxi32!("1.5"));
+
+    // Saturates instead of wrapping
+    assert_eq!((a * b).narrow(), FixedI32::from_bits(i32::MAX));
+    assert_eq!((-a * b).narrow(), FixedI32::from_bits(i32::MIN));
+
+    // Rounds to nearest
+    assert_eq!(
+        FixedI64::from_bits(0x1_8000).narrow(),
+        FixedI32::from_bits(2)
+    );
+    assert_eq!(
+        FixedI64::from_bits(0x1_7fff).narrow(),
+        FixedI32::from_bits(1)
+    );
+}
+
+#[test]
+fn display() {
+    // Between -1 and 0 the whole part alone has no sign to go by
+    assert_eq!(format!("{}", FixedI64::from(fxi32!(-0.5))), "-0.500000000");
+    assert_eq!(format!("{}", FixedI64::from(-3)), "-3");
+    assert_eq!(
+        format!("{:.3}", FixedI64::from(40000 * 40000)),
+        "1600000000.000"
+    );
+    assert_eq!(
+        format!("{:.2}", FixedI64::from_bits(-0x1_ffff_ffff)),
+        "-2.00"
+    );
+}
+
+#[test]
+fn sqrt() {
+    assert_eq!(FixedI64::from(40000 * 40000).sqrt(), FixedI64::from(40000));
+    assert_eq!(FixedI64::from(-4).sqrt(), FixedI64::default());
+
+    // sqrt(2), rounded down to the last of 32 fractional bits
+    assert_eq!(FixedI64::from(2).sqrt(), FixedI64::from_bits(0x1_6a09_e667));
+}
+
+#[test]
+fn far_sphere() {
+    // oc . oc = 40000 would wrap in Q16.16
+    let sphere = Sphere::new(
+        Vec3FI32::new(fxi32!(0), fxi32!(0), fxi32!(-200)),
+        fxi32!(150),
+        None,
+    );
+    let ray = Ray::new(
+        Vec3FI32::default(),
+        Vec3FI32::new(fxi32!(0), fxi32!(0), fxi32!(-1)),
+    );
+    let mut record = HitRecord::default();
+
+    assert!(sphere.hit(&ray, &mut record, fxi32!(0.001), fxi32!(1000)));
+    assert_eq!(record.t, fxi32!(50));
+}
@@ -0,0 +1,85 @@
+use alloc::vec::Vec;
+
+use crate::{filter::Filter, fixed::FixedI32, framebuffer::Framebuffer, fxi32, vec3::Vec3FI32};
+
+#[test]
+fn bright_sums() {
+    let mut fb = Framebuffer::new(1, 1);
+
+    // 1000 * 100 is way past what a FixedI32 can hold
+    for _ in 0..1000 {
+        fb.add_sample(0, Vec3FI32::from(fxi32!(100)), fxi32!(1));
+    }
+
+    assert_eq!(fb.pixel(0).x, fxi32!(100));
+}
+
+#[test]
+fn tiny_contributions() {
+    let mut fb = Framebuffer::new(1, 1);
+
+    fb.add_sample(0, Vec3FI32::from(FixedI32::from_bits(3)), fxi32!(1));
+    for _ in 0..3 {
+        fb.add_sample(0, Vec3FI32::default(), fxi32!(1));
+    }
+
+    // 3/4 of the smallest step rounds up instead of vanishing
+    assert_eq!(fb.pixel(0).y, FixedI32::from_bits(1));
+}
+
+#[test]
+fn tent_splat() {
+    let mut fb = Framebuffer::new(3, 1);
+
+    // Right on the center of the middle pixel, so the neighbours get nothing
+    fb.splat(
+        fxi32!(1.5),
+        fxi32!(0.5),
+        Vec3FI32::from(fxi32!(1)),
+        Filter::Tent,
+    );
+    assert_eq!(fb.pixel(0), Vec3FI32::default());
+    assert_eq!(fb.pixel(1), Vec3FI32::from(fxi32!(1)));
+
+    // Half way between the first two pixels, so both of them average it in
+    fb.splat(
+        fxi32!(1),
+        fxi32!(0.5),
+        Vec3FI32::from(fxi32!(3)),
+        Filter::Tent,
+    );
+    assert_eq!(fb.pixel(0), Vec3FI32::from(fxi32!(3)));
+    assert!((fb.pixel(1).x - fxi32!(5) / 3).abs() <= FixedI32::from_bits(1));
+}
+
+#[test]
+fn merged_regions() {
+    let mut top = Framebuffer::new_region(0, 0, 2, 2);
+    let mut bottom = Framebuffer::new_region(0, 1, 2, 2);
+
+    // Lands on the overlapping row from both sides
+    top.splat(
+        fxi32!(0.5),
+        fxi32!(1.5),
+        Vec3FI32::from(fxi32!(1)),
+        Filter::Box,
+    );
+    bottom.splat(
+        fxi32!(0.5),
+        fxi32!(1.5),
+        Vec3FI32::from(fxi32!(3)),
+        Filter::Box,
+    );
+
+    let mut saved = Vec::new();
+    bottom.write_to(&mut saved).unwrap();
+    let bottom = Framebuffer::read_from(&mut saved.as_slice()).unwrap();
+    assert_eq!(bottom.origin(), (0, 1));
+
+    let mut full = Framebuffer::new(2, 3);
+    full.merge(&top);
+    full.merge(&bottom);
+
+    assert_eq!(full.pixel(2), Vec3FI32::from(fxi32!(2)));
+    assert_eq!(full.pixel(0), Vec3FI32::default());
+}
@@ -0,0 +1,52 @@
+use alloc::vec;
+
+use super::caster::test_renderer;
+use crate::{
+    caster::{RenderControl, RenderMode},
+    filter::Filter,
+    framebuffer::Framebuffer,
+    fxi32, heatmap,
+    stats::RenderStats,
+    tile::Rect,
+    vec3::Vec3FI32,
+};
+
+#[test]
+fn false_color_ends() {
+    assert_eq!(
+        heatmap::false_color(fxi32!(0)),
+        Vec3FI32::new(fxi32!(0), fxi32!(0), fxi32!(0.25))
+    );
+    assert_eq!(
+        heatmap::false_color(fxi32!(1)),
+        Vec3FI32::new(fxi32!(1), fxi32!(0), fxi32!(0))
+    );
+    assert_eq!(
+        heatmap::false_color(fxi32!(5)),
+        heatmap::false_color(fxi32!(1))
+    );
+}
+
+#[test]
+fn hit_test_counts() {
+    let mut renderer = test_renderer(Filter::Mitchell);
+    let mut framebuffer = Framebuffer::new(24, 18);
+    let mut screen_buff = vec![0u16; 24 * 18];
+    let mut rgb_buff = vec![0u8; 24 * 18 * 3];
+
+    renderer.set_mode(RenderMode::HitTests);
+    renderer.render_region(
+        Rect::new(0, 0, 24, 18),
+        &mut framebuffer,
+        None,
+        &mut RenderStats::default(),
+        &mut |_, _| RenderControl::Continue,
+    );
+
+    // The top row only sees the sky, so every sample tests both spheres once
+    assert_eq!(framebuffer.pixel(0), Vec3FI32::from(fxi32!(2)));
+
+    let max = heatmap::resolve(&framebuffer, &mut screen_buff, &mut rgb_buff);
+    assert!(max > fxi32!(2));
+    assert!(rgb_buff.chunks(3).any(|pixel| pixel == [255, 0, 0]));
+}
@@ -0,0 +1,27 @@
+use alloc::{sync::Arc, vec::Vec};
+
+use super::caster::test_renderer;
+use crate::{filter::Filter, stats::RenderStats, threaded::render_threaded};
+
+#[test]
+fn same_image_on_any_thread_count() {
+    let renderer = Arc::new(test_renderer(Filter::Mitchell));
+    let mut images = Vec::new();
+
+    for threads in [1, 2, 5] {
+        let mut saved = Vec::new();
+
+        render_threaded(
+            renderer.clone(),
+            threads,
+            &mut RenderStats::default(),
+            &mut |_, _| {},
+        )
+        .write_to(&mut saved)
+        .unwrap();
+        images.push(saved);
+    }
+
+    assert_eq!(images[0], images[1]);
+    assert_eq!(images[0], images[2]);
+}
@@ -0,0 +1,84 @@
+use crate::{
+    fixed::FixedI32,
+    fxi32,
+    tonemap::{self, srgb_encode, ToneMapper, ToneOperator},
+    vec3::Vec3FI32,
+};
+
+const OPERATORS: [ToneOperator; 4] = [
+    ToneOperator::Clamp,
+    ToneOperator::Reinhard,
+    ToneOperator::AcesFit,
+    ToneOperator::Filmic,
+];
+
+fn map(tone_mapper: &ToneMapper, x: FixedI32) -> FixedI32 {
+    tone_mapper.map(Vec3FI32::from(x)).x
+}
+
+#[test]
+fn operators() {
+    for &operator in OPERATORS.iter() {
+        let tone_mapper = ToneMapper::new(operator, fxi32!(1));
+
+        assert_eq!(map(&tone_mapper, fxi32!(0)), fxi32!(0), "{:?}", operator);
+        assert_eq!(map(&tone_mapper, fxi32!(-1)), fxi32!(0), "{:?}", operator);
+
+        let one = map(&tone_mapper, fxi32!(1));
+        assert!(
+            one > fxi32!(0.5) && one <= fxi32!(1),
+            "{:?}: {}",
+            operator,
+            one
+        );
+
+        // Way past the brightest input they handle, but still white and not wrapped around
+        let bright = map(&tone_mapper, fxi32!(1000));
+        assert!(
+            bright > fxi32!(0.99) && bright <= fxi32!(1),
+            "{:?}: {}",
+            operator,
+            bright
+        );
+    }
+
+    assert_eq!(map(&ToneMapper::default(), fxi32!(1)), fxi32!(1));
+    // Reinhard maps 1 to 0.5, which is 0.7354 in sRGB
+    let reinhard = ToneMapper::new(ToneOperator::Reinhard, fxi32!(1));
+    assert!((map(&reinhard, fxi32!(1)) - fxi32!(0.7354)).abs() < fxi32!(0.002));
+}
+
+#[test]
+fn exposure() {
+    assert_eq!(tonemap::stops(fxi32!(0)), fxi32!(1));
+    assert_eq!(tonemap::stops(fxi32!(3)), fxi32!(8));
+    assert_eq!(tonemap::stops(fxi32!(-2)), fxi32!(0.25));
+    assert_eq!(tonemap::stops(fxi32!(0.5)), fxi32!(1.41421));
+    assert_eq!(tonemap::stops(fxi32!(20)), FixedI32::from_bits(i32::MAX));
+
+    // One stop up is the same as twice the light, two down a quarter
+    let up = ToneMapper::new(ToneOperator::Reinhard, tonemap::stops(fxi32!(1)));
+    let down = ToneMapper::new(ToneOperator::Reinhard, tonemap::stops(fxi32!(-2)));
+    let normal = ToneMapper::new(ToneOperator::Reinhard, fxi32!(1));
+    assert_eq!(map(&up, fxi32!(0.3)), map(&normal, fxi32!(0.6)));
+    assert_eq!(map(&down, fxi32!(2)), map(&normal, fxi32!(0.5)));
+
+    // Bright enough to overflow once scaled, which used to come out black
+    let high = ToneMapper::new(ToneOperator::Clamp, tonemap::stops(fxi32!(12)));
+    assert_eq!(map(&high, fxi32!(1000)), fxi32!(1));
+}
+
+#[test]
+fn srgb_curve() {
+    assert_eq!(srgb_encode(fxi32!(0)), fxi32!(0));
+    assert_eq!(srgb_encode(fxi32!(1)), fxi32!(1));
+    assert_eq!(srgb_encode(fxi32!(-0.5)), fxi32!(0));
+    assert_eq!(srgb_encode(fxi32!(2)), fxi32!(1));
+
+    let mut last = fxi32!(0);
+    for bits in 0..=1 << 16 {
+        let encoded = srgb_encode(FixedI32::from_bits(bits));
+        assert!(encoded >= last, "{} < {} at {}", encoded, last, bits);
+        last = encoded;
+    }
+}
@@ -0,0 +1,79 @@
+use core::f64::consts::PI;
+
+use fixed::{Fixed, FixedI32};
+
+use crate::fxi32;
+
+/// How far `actual` (Q16.16) is from `exact`, in steps of 2^-16.
+fn steps(actual: FixedI32, exact: f64) -> f64 {
+    (actual.to_bits() as f64 - exact * 65536.0).abs()
+}
+
+fn fixed(value: f64) -> FixedI32 {
+    FixedI32::from_bits((value * 65536.0).round() as i32)
+}
+
+fn exact(value: FixedI32) -> f64 {
+    value.to_bits() as f64 / 65536.0
+}
+
+#[test]
+fn sin_cos_tan() {
+    for i in -2000..=2000 {
+        let x = fixed(i as f64 / 100.0);
+        let (sin, cos) = x.sin_cos();
+
+        assert!(steps(sin, exact(x).sin()) <= 1.0, "sin({}) = {}", x, sin);
+        assert!(steps(cos, exact(x).cos()) <= 1.0, "cos({}) = {}", x, cos);
+
+        // tan = sin / cos blows up the errors in both near pi/2
+        let (s, c) = (exact(x).sin(), exact(x).cos());
+        if c.abs() > 0.01 {
+            assert!(
+                steps(x.tan(), s / c) <= 0.5 + 1.0 / c.abs() + s.abs() / (c * c),
+                "tan({x}) = {}",
+                x.tan()
+            );
+        }
+    }
+
+    // The old Taylor series got the sign of negative angles wrong
+    assert!(fxi32!(-1).sin() < fxi32!(-0.84));
+    assert_eq!(FixedI32::PI.sin(), fxi32!(0));
+    assert!((Fixed::<24>::from("0.5").sin().to_f32() - 0.479425538).abs() < 1e-7);
+}
+
+#[test]
+fn inverse() {
+    for i in -100..=100 {
+        let x = fixed(i as f64 / 100.0);
+
+        assert!(
+            steps(x.asin(), exact(x).asin()) <= 2.0,
+            "asin({x}) = {}",
+            x.asin()
+        );
+        assert!(
+            steps(x.acos(), exact(x).acos()) <= 2.0,
+            "acos({x}) = {}",
+            x.acos()
+        );
+    }
+
+    let mut rng = oorandom::Rand32::new(5);
+    for _ in 0..2000 {
+        let y = FixedI32::from_bits(rng.rand_i32() >> (rng.rand_u32() % 16));
+        let x = FixedI32::from_bits(rng.rand_i32() >> (rng.rand_u32() % 16));
+        let exact = exact(y).atan2(exact(x));
+
+        assert!(
+            steps(y.atan2(x), exact) <= 1.0,
+            "atan2({y}, {x}) = {}",
+            y.atan2(x)
+        );
+    }
+
+    assert_eq!(fxi32!(0).atan2(fxi32!(0)), fxi32!(0));
+    assert_eq!(fxi32!(0).atan2(fxi32!(-1)), fixed(PI));
+    assert_eq!(fxi32!(2).asin(), fixed(PI / 2.0));
+}
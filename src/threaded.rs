@@ -0,0 +1,143 @@
+use alloc::{sync::Arc, vec, vec::Vec};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use oorandom::Rand32;
+use std::{env, eprintln, fs, println, sync::mpsc, thread};
+
+use crate::{
+    caster::{RenderControl, Renderer},
+    clock::Stopwatch,
+    export,
+    filter::Filter,
+    framebuffer::Framebuffer,
+    scalar::Scalar,
+    screen::{LCD_HEIGHT, LCD_WIDTH},
+    stats::RenderStats,
+    tile::TileJob,
+    tonemap::ToneMapper,
+};
+
+/// Rows per tile, small enough that the threads finish at about the same time.
+const TILE_ROWS: u16 = 8;
+
+/// Renders the whole image on `threads` threads. The image is cut into bands of `TILE_ROWS` rows
+/// and the threads grab whichever band is next. Every sample is seeded on its own and
+/// framebuffers are just integer sums, so merging the bands in whatever order they finish in gives
+/// exactly the same image for any number of threads.
+///
+/// The threads' stats get added to `stats`, and `progress_callback` gets called on this thread
+/// with the number of finished bands and the total.
+pub fn render_threaded<T, F>(
+    renderer: Arc<Renderer<T>>,
+    threads: usize,
+    stats: &mut RenderStats,
+    progress_callback: &mut F,
+) -> Framebuffer
+where
+    T: Scalar,
+    F: FnMut(usize, usize),
+{
+    let (width, height) = (renderer.width(), renderer.height());
+    let bands = (height + TILE_ROWS - 1) / TILE_ROWS;
+    let jobs = Arc::new(TileJob::bands(width, height, bands));
+    let next_job = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    let workers: Vec<_> = (0..threads.max(1))
+        .map(|_| {
+            let renderer = renderer.clone();
+            let jobs = jobs.clone();
+            let next_job = next_job.clone();
+            let sender = sender.clone();
+
+            thread::spawn(move || {
+                while let Some(job) = jobs.get(next_job.fetch_add(1, Ordering::Relaxed)) {
+                    let mut stats = RenderStats::default();
+                    let tile = job
+                        .render(&renderer, &mut stats, &mut |_, _| RenderControl::Continue)
+                        .unwrap();
+
+                    if sender.send((tile, stats)).is_err() {
+                        break;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    // Otherwise the receiver would keep waiting for this one
+    drop(sender);
+
+    let mut framebuffer = Framebuffer::new(width, height);
+    for (done, (tile, tile_stats)) in receiver.iter().enumerate() {
+        framebuffer.merge(&tile);
+        stats.merge(&tile_stats);
+        progress_callback(done + 1, jobs.len());
+    }
+
+    for worker in workers {
+        worker.join().expect("Render thread panicked");
+    }
+
+    framebuffer
+}
+
+/// The host's way in, since it has none of the calculator's dialogs: `nspray --threads <n>
+/// [--samples <n>]` renders the usual scene at 320x240 on `n` threads and writes it to
+/// `nspray_render.ppm`. Returns false if there's no `--threads`, so that everything else starts
+/// up like it does on the calculator.
+pub fn run_from_args() -> bool {
+    let args: Vec<_> = env::args().collect();
+    let value = |name: &str| {
+        let index = args.iter().position(|arg| arg == name)?;
+        let value = args.get(index + 1).and_then(|value| value.parse().ok());
+
+        Some(value.unwrap_or_else(|| panic!("{} needs a number", name)))
+    };
+
+    let threads = match value("--threads") {
+        Some(threads) => threads,
+        None => return false,
+    };
+    let samples = value("--samples").unwrap_or(16) as u16;
+    let (width, height) = (LCD_WIDTH as u16, LCD_HEIGHT as u16);
+
+    let renderer: Renderer = Renderer::new(
+        crate::gen_camera(width, height, false),
+        crate::gen_scene(&mut Rand32::new(2)),
+        width,
+        height,
+        samples,
+        false,
+        ToneMapper::default(),
+        Filter::Mitchell,
+        crate::RENDER_SEED,
+    );
+
+    let stopwatch = Stopwatch::start();
+    let mut stats = RenderStats::default();
+    let framebuffer = render_threaded(
+        Arc::new(renderer),
+        threads,
+        &mut stats,
+        &mut |done, total| eprintln!("{done}/{total} bands"),
+    );
+
+    let len = width as usize * height as usize;
+    let mut screen_buff = vec![0u16; len];
+    let mut rgb_buff = vec![0u8; len * 3];
+    framebuffer.resolve(&ToneMapper::default(), &mut screen_buff, &mut rgb_buff);
+
+    let mut image = Vec::new();
+    export::write_ppm_header(&mut image, width as usize, height as usize).unwrap();
+    image.extend_from_slice(&rgb_buff);
+    fs::write("nspray_render.ppm", image).expect("Couldn't write nspray_render.ppm");
+
+    println!(
+        "{} samples per pixel on {threads} threads in {:?}, {} rays",
+        samples,
+        stopwatch.elapsed(),
+        stats.primary_rays + stats.bounce_rays
+    );
+
+    true
+}
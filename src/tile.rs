@@ -0,0 +1,102 @@
+use alloc::vec::Vec;
+
+use crate::{
+    caster::{RenderControl, Renderer},
+    framebuffer::Framebuffer,
+    scalar::Scalar,
+    stats::RenderStats,
+};
+
+/// A rectangle of pixels, in pixels from the top-left corner of the image.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl Rect {
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// The rect covering the given rows of a `width` pixel wide image.
+    pub fn rows(first: u16, last: u16, width: u16) -> Self {
+        Self::new(0, first, width, last - first)
+    }
+
+    /// This rect grown by `margin` pixels on every side, but staying inside a `width` x `height`
+    /// image. That's what a framebuffer for this rect needs to cover so that no samples that get
+    /// splatted across the rect's border go missing.
+    pub fn grow(self, margin: u16, width: u16, height: u16) -> Self {
+        let x = self.x.saturating_sub(margin);
+        let y = self.y.saturating_sub(margin);
+
+        Self {
+            x,
+            y,
+            width: (self.x + self.width + margin).min(width) - x,
+            height: (self.y + self.height + margin).min(height) - y,
+        }
+    }
+}
+
+/// One independent piece of a render: the pixels in `rect`. The scene, camera, settings and seed
+/// come from the `Renderer` it gets run with, which has to be set up the same way everywhere a job
+/// runs for the tiles to fit together.
+///
+/// Jobs don't share anything, so they can run on different calculators (or at different times)
+/// and the framebuffers they produce get added back together with `Framebuffer::merge`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileJob {
+    pub rect: Rect,
+}
+
+impl TileJob {
+    /// Splits a `width` x `height` image into `count` bands of (nearly) equal height.
+    pub fn bands(width: u16, height: u16, count: u16) -> Vec<TileJob> {
+        (0..count)
+            .map(|i| {
+                let first = (i as u32 * height as u32 / count as u32) as u16;
+                let last = ((i as u32 + 1) * height as u32 / count as u32) as u16;
+
+                TileJob {
+                    rect: Rect::rows(first, last, width),
+                }
+            })
+            .collect()
+    }
+
+    /// Renders the job into a framebuffer covering its rect plus the filter's reach.
+    /// `progress_callback` gets called after every row, see `Renderer::render_region`. Returns
+    /// `None` if the callback aborted the job, since a partial tile can't be merged.
+    pub fn render<T, F>(
+        &self,
+        renderer: &Renderer<T>,
+        stats: &mut RenderStats,
+        progress_callback: &mut F,
+    ) -> Option<Framebuffer>
+    where
+        T: Scalar,
+        F: FnMut(&Framebuffer, u16) -> RenderControl,
+    {
+        let area = self.rect.grow(
+            renderer.filter_margin(),
+            renderer.width(),
+            renderer.height(),
+        );
+        let mut framebuffer = Framebuffer::new_region(area.x, area.y, area.width, area.height);
+
+        if renderer.render_region(self.rect, &mut framebuffer, None, stats, progress_callback) {
+            Some(framebuffer)
+        } else {
+            None
+        }
+    }
+}
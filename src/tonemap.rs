@@ -0,0 +1,135 @@
+use crate::{fixed::FixedI32, fxi32, vec3::Vec3FI32};
+
+/// Linear to sRGB transfer curve, sampled at 257 evenly spaced points over [0, 1] and scaled to
+/// Q16.16. Interpolating linearly between entries is off by less than half an 8-bit step.
+const SRGB_LUT: [i32; 257] = [
+    0, 3255, 5552, 7237, 8618, 9809, 10868, 11828, 12711, 13531, 14300, 15026, 15713, 16368, 16995,
+    17596, 18173, 18731, 19269, 19790, 20295, 20786, 21264, 21728, 22182, 22624, 23056, 23479,
+    23892, 24297, 24694, 25083, 25466, 25841, 26209, 26571, 26928, 27278, 27623, 27963, 28298,
+    28628, 28953, 29274, 29590, 29903, 30211, 30515, 30816, 31113, 31406, 31696, 31983, 32267,
+    32547, 32825, 33099, 33371, 33640, 33906, 34170, 34431, 34689, 34946, 35199, 35451, 35700,
+    35947, 36192, 36435, 36676, 36914, 37151, 37386, 37619, 37850, 38080, 38307, 38533, 38758,
+    38980, 39201, 39421, 39638, 39855, 40070, 40283, 40495, 40705, 40914, 41122, 41329, 41534,
+    41737, 41940, 42141, 42341, 42540, 42738, 42934, 43129, 43324, 43517, 43709, 43899, 44089,
+    44278, 44466, 44652, 44838, 45023, 45206, 45389, 45571, 45752, 45932, 46111, 46289, 46466,
+    46643, 46818, 46993, 47167, 47339, 47512, 47683, 47854, 48023, 48192, 48361, 48528, 48695,
+    48861, 49026, 49191, 49354, 49517, 49680, 49842, 50003, 50163, 50323, 50482, 50640, 50798,
+    50955, 51111, 51267, 51422, 51577, 51731, 51884, 52037, 52189, 52341, 52492, 52643, 52793,
+    52942, 53091, 53239, 53387, 53534, 53681, 53827, 53973, 54118, 54263, 54407, 54550, 54693,
+    54836, 54978, 55120, 55261, 55402, 55542, 55682, 55821, 55960, 56098, 56236, 56374, 56511,
+    56648, 56784, 56920, 57055, 57190, 57325, 57459, 57592, 57726, 57859, 57991, 58123, 58255,
+    58386, 58517, 58648, 58778, 58908, 59037, 59166, 59295, 59423, 59551, 59678, 59806, 59933,
+    60059, 60185, 60311, 60436, 60561, 60686, 60811, 60935, 61059, 61182, 61305, 61428, 61550,
+    61672, 61794, 61916, 62037, 62158, 62278, 62399, 62518, 62638, 62757, 62876, 62995, 63114,
+    63232, 63350, 63467, 63585, 63702, 63818, 63935, 64051, 64167, 64282, 64398, 64513, 64627,
+    64742, 64856, 64970, 65084, 65197, 65310, 65423, 65536,
+];
+
+// Above this everything maps to white anyway, and it keeps the polynomials below from overflowing.
+const MAX_INPUT: FixedI32 = FixedI32::from_components(64, 0);
+
+/// How HDR radiance gets squeezed into the displayable [0, 1] range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToneOperator {
+    /// Hard clip at 1, which is what the renderer used to do.
+    Clamp,
+    /// x / (1 + x)
+    Reinhard,
+    /// Krzysztof Narkowicz's curve fit of the ACES reference rendering transform.
+    AcesFit,
+    /// John Hable's filmic curve from Uncharted 2.
+    Filmic,
+}
+
+/// Turns the linear color coming out of the tracer into an sRGB encoded color in [0, 1], ready to
+/// be quantised to RGB555/RGB888.
+#[derive(Clone, Copy, Debug)]
+pub struct ToneMapper {
+    operator: ToneOperator,
+    exposure: FixedI32,
+}
+
+impl ToneMapper {
+    /// `exposure` is a linear scale applied before the operator, see [`stops`] for the usual
+    /// photographic units.
+    pub fn new(operator: ToneOperator, exposure: FixedI32) -> Self {
+        Self { operator, exposure }
+    }
+
+    pub fn map(&self, color: Vec3FI32) -> Vec3FI32 {
+        Vec3FI32::new(
+            self.map_channel(color.x),
+            self.map_channel(color.y),
+            self.map_channel(color.z),
+        )
+    }
+
+    fn map_channel(&self, x: FixedI32) -> FixedI32 {
+        // Saturating, or anything bright enough at high exposures wraps around to black
+        let x = x
+            .saturating_mul(self.exposure)
+            .max(fxi32!(0))
+            .min(MAX_INPUT);
+
+        let mapped = match self.operator {
+            ToneOperator::Clamp => x,
+            ToneOperator::Reinhard => x / (x + 1),
+            ToneOperator::AcesFit => aces_fit(x),
+            ToneOperator::Filmic => hable(x * 2) * FILMIC_WHITE_SCALE,
+        };
+
+        srgb_encode(mapped)
+    }
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        Self::new(ToneOperator::Clamp, fxi32!(1))
+    }
+}
+
+/// 2^n as a linear exposure scale. Fractions of a stop work too, and anything too bright to fit
+/// saturates.
+pub fn stops(n: FixedI32) -> FixedI32 {
+    n.exp2()
+}
+
+fn aces_fit(x: FixedI32) -> FixedI32 {
+    let a = FixedI32::from_dec(2, 51, 2);
+    let b = FixedI32::from_dec(0, 3, 2);
+    let c = FixedI32::from_dec(2, 43, 2);
+    let d = FixedI32::from_dec(0, 59, 2);
+    let e = FixedI32::from_dec(0, 14, 2);
+
+    (x * (a * x + b)) / (x * (c * x + d) + e)
+}
+
+// 1 / hable(11.2), so that a linear value of 11.2 ends up as white.
+const FILMIC_WHITE_SCALE: FixedI32 = FixedI32::from_dec(1, 3791, 4);
+
+fn hable(x: FixedI32) -> FixedI32 {
+    let a = FixedI32::from_dec(0, 15, 2);
+    let b = FixedI32::from_dec(0, 50, 2);
+    let c = FixedI32::from_dec(0, 10, 2);
+    let d = FixedI32::from_dec(0, 20, 2);
+    let e = FixedI32::from_dec(0, 2, 2);
+    let f = FixedI32::from_dec(0, 30, 2);
+
+    (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f
+}
+
+/// Applies the sRGB transfer curve to a linear value in [0, 1].
+pub fn srgb_encode(x: FixedI32) -> FixedI32 {
+    let x = x.to_bits().clamp(0, 1 << 16);
+
+    // 8 bits of index, 8 bits to interpolate with
+    let index = (x >> 8) as usize;
+    if index >= SRGB_LUT.len() - 1 {
+        return fxi32!(1);
+    }
+
+    let lo = SRGB_LUT[index];
+    let hi = SRGB_LUT[index + 1];
+
+    FixedI32::from_bits(lo + (((hi - lo) * (x & 0xff)) >> 8))
+}
@@ -0,0 +1,164 @@
+use crate::fixed::{isqrt, Fixed};
+
+/// Fractional bits of the angles and vectors CORDIC works with internally. Leaves room for +/- 4,
+/// which covers both +/- pi and the gain the vectors pick up on the way. Formats with more
+/// fractional bits than this can't use these.
+const CORDIC_FRAC: u32 = 29;
+
+/// atan(2^-i) in Q3.29.
+const ATAN_STEPS: [i32; 30] = [
+    421657428, 248918915, 131521918, 66762579, 33510843, 16771758, 8387925, 4194219, 2097141,
+    1048575, 524288, 262144, 131072, 65536, 32768, 16384, 8192, 4096, 2048, 1024, 512, 256, 128,
+    64, 32, 16, 8, 4, 2, 1,
+];
+
+/// 1 / the length every vector gets stretched by over all the steps, in Q3.29.
+const GAIN_INV: i32 = 326016437;
+
+const PI: i32 = 1686629713;
+const HALF_PI: i32 = 843314857;
+
+/// Rotates (`x`, `y`) by `angle` (all Q3.29, angle within +/- pi/2), stretched by 1 / GAIN_INV.
+fn rotate(mut x: i32, mut y: i32, mut angle: i32) -> (i32, i32) {
+    for (i, step) in ATAN_STEPS.iter().enumerate() {
+        let (dx, dy) = (y >> i, x >> i);
+
+        if angle >= 0 {
+            x -= dx;
+            y += dy;
+            angle -= step;
+        } else {
+            x += dx;
+            y -= dy;
+            angle += step;
+        }
+    }
+
+    (x, y)
+}
+
+/// The angle of (`x`, `y`) for `x` >= 0, in Q3.29. Only the ratio of the two matters.
+fn vector_angle(mut x: i32, mut y: i32) -> i32 {
+    let mut angle = 0;
+
+    for (i, step) in ATAN_STEPS.iter().enumerate() {
+        let (dx, dy) = (y >> i, x >> i);
+
+        if y < 0 {
+            x -= dx;
+            y += dy;
+            angle -= step;
+        } else {
+            x += dx;
+            y -= dy;
+            angle += step;
+        }
+    }
+
+    angle
+}
+
+impl<const FRAC: u32> Fixed<FRAC> {
+    /// Sine and cosine at once, which is what CORDIC gives anyway. Both are within 2^-26 of the
+    /// exact value before rounding to this format.
+    pub fn sin_cos(self) -> (Self, Self) {
+        // Into Q3.29 and down to [-pi, pi]
+        let two_pi = 2 * PI as i64;
+        let mut angle = ((self.to_bits() as i64) << (CORDIC_FRAC - FRAC)) % two_pi;
+        if angle > PI as i64 {
+            angle -= two_pi;
+        } else if angle < -PI as i64 {
+            angle += two_pi;
+        }
+
+        // CORDIC only converges up to about pi/2 either way, so mirror the rest over the y axis
+        let mut angle = angle as i32;
+        let mut cos_sign = 1;
+        if angle > HALF_PI {
+            angle = PI - angle;
+            cos_sign = -1;
+        } else if angle < -HALF_PI {
+            angle = -PI - angle;
+            cos_sign = -1;
+        }
+
+        let (cos, sin) = rotate(GAIN_INV, 0, angle);
+
+        (Self::from_cordic(sin), Self::from_cordic(cos * cos_sign))
+    }
+
+    pub fn sin(self) -> Self {
+        self.sin_cos().0
+    }
+
+    pub fn cos(self) -> Self {
+        self.sin_cos().1
+    }
+
+    /// Saturates instead of dividing by 0 at +/- pi/2.
+    pub fn tan(self) -> Self {
+        let (sin, cos) = self.sin_cos();
+        sin.saturating_div(cos)
+    }
+
+    /// The angle between the positive x axis and (`x`, `self`), in [-pi, pi] like `f32::atan2`.
+    /// Within 2^-26 of the exact angle before rounding. (0, 0) gives 0.
+    pub fn atan2(self, x: Self) -> Self {
+        let (mut x, mut y) = (x.to_bits() as i64, self.to_bits() as i64);
+
+        if x == 0 && y == 0 {
+            return Self::from_bits(0);
+        }
+
+        // Vectoring needs x >= 0, so turn the left half plane around by pi first
+        let mut offset = 0;
+        if x < 0 {
+            x = -x;
+            y = -y;
+            offset = if y > 0 { -PI } else { PI };
+        }
+
+        // Scale so the bigger of the two is in [2^27, 2^28), so that neither the stretching nor
+        // the rotation can overflow and small vectors don't lose all their bits
+        let shift = 36 - (x.abs().max(y.abs()) as u64).leading_zeros() as i32;
+        let (x, y) = if shift >= 0 {
+            ((x >> shift) as i32, (y >> shift) as i32)
+        } else {
+            ((x << -shift) as i32, (y << -shift) as i32)
+        };
+
+        Self::from_cordic(vector_angle(x, y) + offset)
+    }
+
+    /// Clamps anything outside of [-1, 1]. The result is as good as `atan2`'s, but near +/- 1
+    /// the input itself only pins the angle down so far.
+    pub fn asin(self) -> Self {
+        let x = self.max(Self::from(-1)).min(Self::from(1));
+        x.atan2(Self::cathetus(x))
+    }
+
+    /// Clamps anything outside of [-1, 1], see `asin`.
+    pub fn acos(self) -> Self {
+        let x = self.max(Self::from(-1)).min(Self::from(1));
+        Self::cathetus(x).atan2(x)
+    }
+
+    /// sqrt(1 - x^2) for x in [-1, 1]. (1 - x)(1 + x) is exact with twice the fractional bits,
+    /// which keeps all the bits near +/- 1 where the square root magnifies every error.
+    fn cathetus(x: Self) -> Self {
+        let (v, one) = (x.to_bits() as i64, 1i64 << FRAC);
+
+        Self::from_bits(isqrt(((one - v) * (one + v)) as u64) as i32)
+    }
+
+    /// Rounds a Q3.29 value to this format.
+    fn from_cordic(value: i32) -> Self {
+        let shift = CORDIC_FRAC - FRAC;
+
+        if shift == 0 {
+            Self::from_bits(value)
+        } else {
+            Self::from_bits((value + (1 << (shift - 1))) >> shift)
+        }
+    }
+}
@@ -6,11 +6,11 @@ use core::{
 use alloc::format;
 use oorandom::Rand32;
 
-use crate::{fixed::FixedI32, fxi32};
+use crate::{fixed::FixedI32, fxi32, scalar::Scalar};
 
 pub type Vec3FI32 = Vec3<FixedI32>;
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Vec3<T> {
     pub x: T,
     pub y: T,
@@ -51,16 +51,16 @@ where
     }
 }
 
-impl Vec3<FixedI32> {
+impl<T: Scalar> Vec3<T> {
     pub fn random_in_unit_sphere(rand: &mut Rand32) -> Self {
         loop {
             let v = Self {
-                x: FixedI32::rand(rand),
-                y: FixedI32::rand(rand),
-                z: FixedI32::rand(rand),
+                x: T::rand(rand),
+                y: T::rand(rand),
+                z: T::rand(rand),
             };
 
-            if v.mag_squared() <= fxi32!(1) {
+            if v.mag_squared() <= T::ONE {
                 return v;
             }
         }
@@ -69,27 +69,36 @@ impl Vec3<FixedI32> {
     pub fn random_in_unit_disk(rand: &mut Rand32) -> Self {
         loop {
             let v = Self {
-                x: FixedI32::rand(rand),
-                y: FixedI32::rand(rand),
-                z: fxi32!(0),
+                x: T::rand(rand),
+                y: T::rand(rand),
+                z: T::ZERO,
             };
 
-            if v.mag_squared() <= fxi32!(1) {
+            if v.mag_squared() <= T::ONE {
                 return v;
             }
         }
     }
 
-    pub fn mag(self) -> FixedI32 {
+    pub fn mag(self) -> T {
         self.mag_squared().sqrt()
     }
 
     pub fn unit_vector(self) -> Self {
-        self / self.mag()
+        self * self.mag_squared().rsqrt()
     }
 
     pub fn near_zero(&self) -> bool {
-        self.x.abs() < fxi32!(0.001) && self.y.abs() < fxi32!(0.001) && self.z.abs() < fxi32!(0.001)
+        let limit = T::from_fixed(fxi32!(0.001));
+        self.x.abs() < limit && self.y.abs() < limit && self.z.abs() < limit
+    }
+
+    pub fn from_fixed(v: Vec3FI32) -> Self {
+        Self::new(T::from_fixed(v.x), T::from_fixed(v.y), T::from_fixed(v.z))
+    }
+
+    pub fn to_fixed(self) -> Vec3FI32 {
+        Vec3FI32::new(self.x.to_fixed(), self.y.to_fixed(), self.z.to_fixed())
     }
 }
 